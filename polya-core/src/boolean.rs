@@ -0,0 +1,275 @@
+use crate::{calc, constants, math::Pos2};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BooleanOp {
+    Union,
+    Intersection,
+    Difference,
+    Xor,
+}
+
+// a closed polyline, the last point connects back to the first one
+pub type Contour = Vec<Pos2>;
+
+// where an edge of one region lies relative to the other region
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Class {
+    Inside,
+    Outside,
+    // on the boundary of the other region, with its interior on the same side
+    SameBoundary,
+    // on the boundary of the other region, with its interior on the other side
+    OppositeBoundary,
+}
+
+fn edges(contour: &[Pos2]) -> impl Iterator<Item = (Pos2, Pos2)> + '_ {
+    (0..contour.len()).map(|i| (contour[i], contour[(i + 1) % contour.len()]))
+}
+
+pub fn signed_area(contour: &[Pos2]) -> f64 {
+    edges(contour)
+        .map(|(a, b)| calc::cross(a.to_vec2(), b.to_vec2()))
+        .sum::<f64>()
+        / 2.0
+}
+
+// even-odd rule: p is inside if a ray from p crosses the contours an odd number of times
+pub fn contains(contours: &[Contour], p: Pos2) -> bool {
+    let mut inside = false;
+    for contour in contours {
+        for (a, b) in edges(contour) {
+            if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+                inside = !inside;
+            }
+        }
+    }
+
+    inside
+}
+
+// orients the contours so that the interior of the region is always on the same side:
+// outer boundaries get a positive signed area and holes a negative one
+fn normalize(contours: &[Contour]) -> Vec<Contour> {
+    contours
+        .iter()
+        .enumerate()
+        .filter(|(_, contour)| contour.len() >= 3)
+        .map(|(i, contour)| {
+            let depth = contours
+                .iter()
+                .enumerate()
+                .filter(|&(j, other)| j != i && contains(std::slice::from_ref(other), contour[0]))
+                .count();
+            let is_hole = depth % 2 == 1;
+            let mut contour = contour.clone();
+            if (signed_area(&contour) < 0.0) != is_hole {
+                contour.reverse();
+            }
+            contour
+        })
+        .collect()
+}
+
+// splits the edges of a region at every point where an edge of the other region
+// crosses or touches them, so that each piece lies entirely inside, outside
+// or on the boundary of the other region
+fn split_edges(region: &[Contour], other: &[Contour]) -> Vec<(Pos2, Pos2)> {
+    let other_edges: Vec<_> = other.iter().flat_map(|c| edges(c)).collect();
+    let mut pieces = Vec::new();
+    for contour in region {
+        for (a, b) in edges(contour) {
+            let mut cuts = vec![a, b];
+            for &(c, d) in &other_edges {
+                if let Some(p) = calc::segments_intersection(a, b, c, d) {
+                    cuts.push(p);
+                }
+                // collinear overlaps have no single intersection point,
+                // but the endpoints of the overlap still need to be cuts
+                for q in [c, d] {
+                    if calc::distance_to_segment(q, a, b) < constants::BOOLEAN_EPS {
+                        cuts.push(q);
+                    }
+                }
+            }
+            let dir = b - a;
+            cuts.sort_by(|p, q| (*p - a).dot(dir).total_cmp(&(*q - a).dot(dir)));
+            cuts.dedup_by(|p, q| p.distance(*q) < constants::BOOLEAN_EPS);
+            pieces.extend(cuts.windows(2).map(|pair| (pair[0], pair[1])));
+        }
+    }
+
+    pieces
+}
+
+fn classify(edge: (Pos2, Pos2), other: &[Contour]) -> Class {
+    let m = calc::midpoint(edge.0, edge.1);
+    let on_boundary = other
+        .iter()
+        .flat_map(|c| edges(c))
+        .find(|&(c, d)| calc::distance_to_segment(m, c, d) < constants::BOOLEAN_EPS);
+    match on_boundary {
+        Some((c, d)) if (edge.1 - edge.0).dot(d - c) > 0.0 => Class::SameBoundary,
+        Some(_) => Class::OppositeBoundary,
+        None if contains(other, m) => Class::Inside,
+        None => Class::Outside,
+    }
+}
+
+// joins the edges into closed contours by following matching endpoints,
+// taking the sharpest turn when there is more than one way to continue
+fn chain(mut pieces: Vec<(Pos2, Pos2)>) -> Vec<Contour> {
+    let mut contours = Vec::new();
+    while let Some((start, mut end)) = pieces.pop() {
+        let mut contour = vec![start];
+        let mut dir = end - start;
+        let closed = loop {
+            if end.distance(start) < constants::BOOLEAN_EPS {
+                break true;
+            }
+            contour.push(end);
+            let next = pieces
+                .iter()
+                .enumerate()
+                .filter(|(_, (a, _))| a.distance(end) < constants::BOOLEAN_EPS)
+                .min_by(|(_, (_, b0)), (_, (_, b1))| {
+                    let turn = |b: Pos2| {
+                        let out = b - end;
+                        calc::cross(dir, out).atan2(dir.dot(out))
+                    };
+                    turn(*b0).total_cmp(&turn(*b1))
+                })
+                .map(|(i, _)| i);
+            let Some(i) = next else {
+                break false;
+            };
+            let (_, b) = pieces.swap_remove(i);
+            dir = b - end;
+            end = b;
+        };
+        if closed {
+            contours.push(contour);
+        }
+    }
+
+    contours
+}
+
+// removes vertices lying on the line through their neighbors
+fn simplify(contour: Contour) -> Contour {
+    let n = contour.len();
+    (0..n)
+        .filter(|&i| {
+            let (prev, p, next) = (contour[(i + n - 1) % n], contour[i], contour[(i + 1) % n]);
+            calc::cross(p - prev, next - p).abs() > constants::BOOLEAN_EPS * prev.distance(next)
+        })
+        .map(|i| contour[i])
+        .collect()
+}
+
+// computes the boolean combination of two regions, each given by contours
+// with the even-odd fill rule; in the result outer contours have positive
+// signed area and holes have negative signed area
+pub fn apply(op: BooleanOp, a: &[Contour], b: &[Contour]) -> Vec<Contour> {
+    use BooleanOp as Op;
+    use Class as C;
+
+    let (a, b) = (normalize(a), normalize(b));
+    let mut pieces = Vec::new();
+    for (edge, class) in split_edges(&a, &b)
+        .into_iter()
+        .map(|e| (e, classify(e, &b)))
+    {
+        match (op, class) {
+            (Op::Union | Op::Difference | Op::Xor, C::Outside)
+            | (Op::Intersection, C::Inside)
+            | (Op::Union | Op::Intersection, C::SameBoundary)
+            | (Op::Difference, C::OppositeBoundary) => pieces.push(edge),
+            (Op::Xor, C::Inside) => pieces.push((edge.1, edge.0)),
+            _ => (),
+        }
+    }
+    // shared boundaries were already taken care of when going through a's edges
+    for (edge, class) in split_edges(&b, &a)
+        .into_iter()
+        .map(|e| (e, classify(e, &a)))
+    {
+        match (op, class) {
+            (Op::Union | Op::Xor, C::Outside) | (Op::Intersection, C::Inside) => pieces.push(edge),
+            (Op::Difference | Op::Xor, C::Inside) => pieces.push((edge.1, edge.0)),
+            _ => (),
+        }
+    }
+    pieces.retain(|(p, q)| p.distance(*q) >= constants::BOOLEAN_EPS);
+
+    chain(pieces)
+        .into_iter()
+        .map(simplify)
+        .filter(|c| c.len() >= 3 && signed_area(c).abs() >= constants::EPS)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f64, y: f64, size: f64) -> Contour {
+        vec![
+            Pos2::new(x, y),
+            Pos2::new(x + size, y),
+            Pos2::new(x + size, y + size),
+            Pos2::new(x, y + size),
+        ]
+    }
+
+    fn area(contours: &[Contour]) -> f64 {
+        contours.iter().map(|c| signed_area(c)).sum()
+    }
+
+    #[test]
+    fn apply_combines_overlapping_squares() {
+        let (a, b) = ([square(0.0, 0.0, 100.0)], [square(50.0, 50.0, 100.0)]);
+        for (op, expected) in [
+            (BooleanOp::Union, 17500.0),
+            (BooleanOp::Intersection, 2500.0),
+            (BooleanOp::Difference, 7500.0),
+            (BooleanOp::Xor, 15000.0),
+        ] {
+            let result = apply(op, &a, &b);
+            assert!(
+                (area(&result) - expected).abs() < 1e-6,
+                "{op:?}: {}",
+                area(&result)
+            );
+            for (p, inside) in [
+                (Pos2::new(25.0, 25.0), op != BooleanOp::Intersection),
+                (
+                    Pos2::new(75.0, 75.0),
+                    matches!(op, BooleanOp::Union | BooleanOp::Intersection),
+                ),
+                (
+                    Pos2::new(125.0, 125.0),
+                    matches!(op, BooleanOp::Union | BooleanOp::Xor),
+                ),
+                (Pos2::new(125.0, 25.0), false),
+            ] {
+                assert_eq!(contains(&result, p), inside, "{op:?} at {p:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn apply_cuts_holes() {
+        let result = apply(
+            BooleanOp::Difference,
+            &[square(0.0, 0.0, 100.0)],
+            &[square(25.0, 25.0, 50.0)],
+        );
+        assert_eq!(result.len(), 2);
+        assert!((area(&result) - 7500.0).abs() < 1e-6);
+        assert!(!contains(&result, Pos2::new(50.0, 50.0)));
+        // a smaller square fills part of the hole back in
+        let result = apply(BooleanOp::Union, &result, &[square(40.0, 40.0, 20.0)]);
+        assert!((area(&result) - 7900.0).abs() < 1e-6);
+        assert!(contains(&result, Pos2::new(50.0, 50.0)));
+    }
+}
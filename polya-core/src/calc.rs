@@ -0,0 +1,653 @@
+use std::f64::consts;
+
+use crate::{
+    constants,
+    math::{Pos2, Vec2},
+    vertex::{Vertex, VertexConstraint},
+};
+
+pub fn midpoint(p0: Pos2, p1: Pos2) -> Pos2 {
+    Pos2::new((p0.x + p1.x) / 2.0, (p0.y + p1.y) / 2.0)
+}
+
+// project p onto the up-diagonal [/] going through s
+pub fn project_onto_diagonal_up(s: Pos2, p: Pos2) -> Pos2 {
+    let tangent = Vec2::new(consts::SQRT_2 / 2.0, -consts::SQRT_2 / 2.0);
+    s + tangent.dot(p - s) * tangent
+}
+
+// project p onto the down-diagonal [\] going through s
+pub fn project_onto_diagonal_down(s: Pos2, p: Pos2) -> Pos2 {
+    let tangent = Vec2::new(consts::SQRT_2 / 2.0, consts::SQRT_2 / 2.0);
+    s + tangent.dot(p - s) * tangent
+}
+
+// project p onto line ab
+pub fn project_onto_line(a: Pos2, b: Pos2, p: Pos2) -> Pos2 {
+    let tangent = (b - a).normalized();
+    a + tangent.dot(p - a) * tangent
+}
+
+pub fn reflection(s: Pos2, p: Pos2) -> Pos2 {
+    s - (p - s)
+}
+
+// rescale vector sp to length r
+pub fn rescale(s: Pos2, p: Pos2, r: f64) -> Pos2 {
+    s + (p - s).normalized() * r
+}
+
+pub fn are_colinear(a: Pos2, b: Pos2, c: Pos2) -> bool {
+    let (v1, v2) = (b - a, c - b);
+    // the cross product is the sine of the angle between them scaled by both lengths
+    cross(v1, v2).abs() <= constants::CONSTRAINT_DIR_TOLERANCE * v1.length() * v2.length()
+}
+
+// are p and q symmetric about s
+pub fn are_reflections(p: Pos2, q: Pos2, s: Pos2) -> bool {
+    let (v1, v2) = (s - p, s - q);
+    (v1 + v2).length() < constants::CONSTRAINT_DIST_TOLERANCE
+}
+
+// move the free point so that G1 holds
+// when goind free -> fixed -> Bézier segment
+pub fn keep_g1(fixed: Pos2, free: Pos2, control: Pos2) -> Pos2 {
+    let len = (fixed - free).length();
+    fixed + len * (fixed - control).normalized()
+}
+
+// check if G1 holds when going p0 -> p1 -> Bézier
+// (points are colinear and in order)
+pub fn check_g1(p0: Pos2, p1: Pos2, control: Pos2) -> bool {
+    let (u, v) = ((p0 - p1).normalized(), (control - p1).normalized());
+    (u.dot(v) - (-1.0)).abs() < constants::CONSTRAINT_DIR_TOLERANCE
+}
+
+// move the control point so that G1 holds
+// when going p0 -> p1 -> Bézier
+pub fn enforce_g1(p0: Pos2, p1: Pos2, control: Pos2) -> Pos2 {
+    let tangent = (p1 - p0).normalized();
+    p1 + (control - p1).dot(tangent) * tangent
+}
+
+// move the free point so that C1 holds
+// when going free -> fixed -> Bézier segment
+pub fn keep_c1(fixed: Pos2, control: Pos2) -> Pos2 {
+    fixed + 3.0 * (fixed - control)
+}
+
+// check if C1 holds when going p0 -> p1 -> Bézier
+// (points are colinear, in order and distances are in ratio 1:3)
+pub fn check_c1(p0: Pos2, p1: Pos2, control: Pos2) -> bool {
+    let (u, v) = ((p0 - p1).normalized(), (control - p1).normalized());
+    (u.dot(v) - (-1.0)).abs() < constants::CONSTRAINT_DIR_TOLERANCE
+        && (p0.distance(p1) / (3.0 * p1.distance(control)) - 1.0).abs()
+            < constants::CONSTRAINT_DIR_TOLERANCE
+}
+
+// move the control point so that C1 holds
+// when going p0 -> p1 -> Bézier
+pub fn enforce_c1(p0: Pos2, p1: Pos2) -> Pos2 {
+    let v = p1 - p0;
+    p1 + v / 3.0
+}
+
+pub fn cross(u: Vec2, v: Vec2) -> f64 {
+    u.x * v.y - u.y * v.x
+}
+
+// signed curvature at the end p of a Bézier curve with control points c0, c1
+pub fn cubic_bezier_end_curvature(c0: Pos2, c1: Pos2, p: Pos2) -> f64 {
+    let d = p - c1;
+    let len = d.length();
+    if len < constants::EPS {
+        return 0.0;
+    }
+
+    (2.0 / 3.0) * cross(d, c0 - c1) / (len * len * len)
+}
+
+// move the second control point b2 so that G2 holds
+// when going Bézier (.., a1, a2, p) -> Bézier (p, b1, b2, ..)
+// (the tangential component of b2 is kept, the normal one sets the curvature)
+pub fn enforce_g2(a1: Pos2, a2: Pos2, p: Pos2, b1: Pos2, b2: Pos2) -> Pos2 {
+    let k = cubic_bezier_end_curvature(a1, a2, p);
+    let b = b1.distance(p);
+    if b < constants::EPS {
+        return b2;
+    }
+    let u = (b1 - p) / b;
+    let n = Vec2::new(-u.y, u.x);
+
+    p + (b2 - p).dot(u) * u + 1.5 * k * b * b * n
+}
+
+// move the second control point so that C2 holds
+// when going Bézier (.., a1, a2, p) -> Bézier (p, b1, b2, ..)
+// (second derivatives a1 - 2 * a2 + p and p - 2 * b1 + b2 are equal)
+pub fn enforce_c2(a1: Pos2, a2: Pos2, b1: Pos2) -> Pos2 {
+    a1 + 2.0 * (b1 - a2)
+}
+
+// integral of f over [a, b] using 5-point Gauss-Legendre quadrature
+// (exact for polynomials of degree up to 9)
+pub fn integrate(f: impl Fn(f64) -> f64, a: f64, b: f64) -> f64 {
+    const NODES: [(f64, f64); 5] = [
+        (0.0, 0.568_888_888_888_888_9),
+        (-0.538_469_310_105_683_1, 0.478_628_670_499_366_5),
+        (0.538_469_310_105_683_1, 0.478_628_670_499_366_5),
+        (-0.906_179_845_938_664, 0.236_926_885_056_189_1),
+        (0.906_179_845_938_664, 0.236_926_885_056_189_1),
+    ];
+    let (mid, half) = ((a + b) / 2.0, (b - a) / 2.0);
+
+    half * NODES
+        .iter()
+        .map(|&(x, w)| w * f(mid + half * x))
+        .sum::<f64>()
+}
+
+// real roots of a * t^2 + b * t + c = 0
+pub fn quadratic_roots(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a.abs() < constants::DOT_EPS {
+        return if b.abs() < constants::DOT_EPS {
+            Vec::new()
+        } else {
+            vec![-c / b]
+        };
+    }
+    let delta = b * b - 4.0 * a * c;
+    if delta < 0.0 {
+        return Vec::new();
+    }
+    let sqrt_delta = delta.sqrt();
+
+    vec![(-b - sqrt_delta) / (2.0 * a), (-b + sqrt_delta) / (2.0 * a)]
+}
+
+pub fn bresenham_points(p0: Pos2, p1: Pos2) -> Vec<Pos2> {
+    let (mut x0, mut y0, mut x1, mut y1) = (p0.x as i32, p0.y as i32, p1.x as i32, p1.y as i32);
+    // if dy > dx, the line goes through octants 2/3/6/7
+    // so we switch the axes to make it go through octants 1/4/5/8
+    let switch = (y1 - y0).abs() > (x1 - x0).abs();
+    if switch {
+        (x0, y0) = (y0, x0);
+        (x1, y1) = (y1, x1);
+    }
+    // if x0 > x1, the line goes "backwards", so we switch the endpoints
+    if x0 > x1 {
+        (x0, x1) = (x1, x0);
+        (y0, y1) = (y1, y0);
+    }
+    // at this point dx >= dy and x1 > x0, so we have a line going through octant 1 or 8
+
+    let (dx, dy) = (x1 - x0, (y1 - y0).abs());
+    let mut y = y0;
+    let mut d = 2 * dy - dx;
+    let incr_e = 2 * dy;
+    let incr_ne = 2 * (dy - dx);
+    let y_step = if y0 < y1 { 1 } else { -1 };
+
+    let mut points = Vec::new();
+    for x in x0..=x1 {
+        if switch {
+            points.push(Pos2::new(y as f64, x as f64));
+        } else {
+            points.push(Pos2::new(x as f64, y as f64));
+        }
+
+        if d < 0 {
+            d += incr_e;
+        } else {
+            y += y_step;
+            d += incr_ne;
+        }
+    }
+
+    points
+}
+
+// distance from p to the segment ab
+pub fn distance_to_segment(p: Pos2, a: Pos2, b: Pos2) -> f64 {
+    let ab = b - a;
+    let len_sq = ab.length_sq();
+    if len_sq < constants::EPS * constants::EPS {
+        return p.distance(a);
+    }
+    let t = ((p - a).dot(ab) / len_sq).clamp(0.0, 1.0);
+    p.distance(a + t * ab)
+}
+
+// intersection point of segments ab and cd, if there is exactly one
+pub fn segments_intersection(a: Pos2, b: Pos2, c: Pos2, d: Pos2) -> Option<Pos2> {
+    let (r, s) = (b - a, d - c);
+    let den = cross(r, s);
+    if den.abs() < constants::DOT_EPS {
+        return None;
+    }
+    let t = cross(c - a, s) / den;
+    let u = cross(c - a, r) / den;
+
+    ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then(|| a + t * r)
+}
+
+// distance from p to the polyline going through the given points
+pub fn distance_to_polyline(p: Pos2, points: &[Pos2]) -> f64 {
+    match points {
+        [] => f64::INFINITY,
+        [q] => p.distance(*q),
+        _ => points
+            .windows(2)
+            .map(|pair| distance_to_segment(p, pair[0], pair[1]))
+            .fold(f64::INFINITY, f64::min),
+    }
+}
+
+// splits the Bézier curve p0-c0-c1-p1 at parameter t (de Casteljau's algorithm)
+// returns control polygons of both halves
+pub fn split_cubic_bezier(
+    p0: Pos2,
+    c0: Pos2,
+    c1: Pos2,
+    p1: Pos2,
+    t: f64,
+) -> ([Pos2; 4], [Pos2; 4]) {
+    let (a, b, c) = (p0.lerp(c0, t), c0.lerp(c1, t), c1.lerp(p1, t));
+    let (d, e) = (a.lerp(b, t), b.lerp(c, t));
+    let m = d.lerp(e, t);
+
+    ([p0, a, d, m], [m, e, c, p1])
+}
+
+pub fn cubic_bezier_point(p0: Pos2, c0: Pos2, c1: Pos2, p1: Pos2, t: f64) -> Pos2 {
+    let s = 1.0 - t;
+    let (p0, c0, c1, p1) = (p0.to_vec2(), c0.to_vec2(), c1.to_vec2(), p1.to_vec2());
+    (s * s * s * p0 + 3.0 * s * s * t * c0 + 3.0 * s * t * t * c1 + t * t * t * p1).to_pos2()
+}
+
+// first and second derivatives of the Bézier curve at parameter t
+pub fn cubic_bezier_derivatives(p0: Pos2, c0: Pos2, c1: Pos2, p1: Pos2, t: f64) -> (Vec2, Vec2) {
+    let s = 1.0 - t;
+    let d1 = 3.0 * (s * s * (c0 - p0) + 2.0 * s * t * (c1 - c0) + t * t * (p1 - c1));
+    let d2 = 6.0 * (s * (c1 - c0 - (c0 - p0)) + t * (p1 - c1 - (c1 - c0)));
+
+    (d1, d2)
+}
+
+// returns the unit tangent and the signed curvature of the Bézier curve at parameter t
+// (for positive curvature the center of curvature lies towards -tangent.rot90())
+pub fn cubic_bezier_curvature(p0: Pos2, c0: Pos2, c1: Pos2, p1: Pos2, t: f64) -> (Vec2, f64) {
+    let (d1, d2) = cubic_bezier_derivatives(p0, c0, c1, p1, t);
+    let len = d1.length();
+    if len < constants::EPS {
+        return (Vec2::ZERO, 0.0);
+    }
+
+    (d1 / len, cross(d1, d2) / (len * len * len))
+}
+
+// returns the parameter of the point on the Bézier curve nearest to p
+// (coarse sampling followed by a golden-section search around the best sample)
+pub fn nearest_cubic_bezier_parameter(p0: Pos2, c0: Pos2, c1: Pos2, p1: Pos2, p: Pos2) -> f64 {
+    let dist_sq = |t: f64| cubic_bezier_point(p0, c0, c1, p1, t).distance_sq(p);
+    let n = constants::BEZIER_PROJECTION_SAMPLES;
+    let best_k = (0..=n)
+        .min_by(|&a, &b| dist_sq(a as f64 / n as f64).total_cmp(&dist_sq(b as f64 / n as f64)))
+        .unwrap_or(0);
+
+    let inv_phi = (5.0_f64.sqrt() - 1.0) / 2.0;
+    let mut lo = (best_k as f64 - 1.0).max(0.0) / n as f64;
+    let mut hi = (best_k as f64 + 1.0).min(n as f64) / n as f64;
+    for _ in 0..constants::BEZIER_PROJECTION_ITERS {
+        let t0 = hi - inv_phi * (hi - lo);
+        let t1 = lo + inv_phi * (hi - lo);
+        if dist_sq(t0) < dist_sq(t1) {
+            hi = t1;
+        } else {
+            lo = t0;
+        }
+    }
+
+    (lo + hi) / 2.0
+}
+
+// the curve lies in the convex hull of its control polygon, so if both
+// control points are close enough to the chord, so is the whole curve
+fn is_flat(p0: Pos2, c0: Pos2, c1: Pos2, p1: Pos2, tolerance: f64) -> bool {
+    distance_to_segment(c0, p0, p1) <= tolerance && distance_to_segment(c1, p0, p1) <= tolerance
+}
+
+fn flatten_cubic_bezier(
+    p0: Pos2,
+    c0: Pos2,
+    c1: Pos2,
+    p1: Pos2,
+    tolerance: f64,
+    depth: u8,
+    points: &mut Vec<Pos2>,
+) {
+    if depth >= constants::MAX_FLATTENING_DEPTH || is_flat(p0, c0, c1, p1, tolerance) {
+        points.push(p1);
+        return;
+    }
+    let (l, r) = split_cubic_bezier(p0, c0, c1, p1, 0.5);
+    flatten_cubic_bezier(l[0], l[1], l[2], l[3], tolerance, depth + 1, points);
+    flatten_cubic_bezier(r[0], r[1], r[2], r[3], tolerance, depth + 1, points);
+}
+
+// returns points of a polyline that is at most `tolerance` away from the Bézier curve
+pub fn cubic_bezier_points(p0: Pos2, p1: Pos2, c0: Pos2, c1: Pos2, tolerance: f64) -> Vec<Pos2> {
+    let mut points = vec![p0];
+    flatten_cubic_bezier(p0, c0, c1, p1, tolerance, 0, &mut points);
+
+    points
+}
+
+// returns parameters of the arc from edge prev_p-p0 to p1-(somewhere)
+// if p0 (the starting vertex) has G1 continuity
+pub fn circular_arc_data_with_g1(p0: Pos2, p1: Pos2, prev_p: Pos2) -> (Pos2, f64) {
+    let m = midpoint(p0, p1);
+    let c = p1 - p0; // vector in the direction of the chord (from p0 to p1)
+    let c_n = Vec2::new(-c.y, c.x); // normal to c
+    let t = p0 - prev_p; // tangent to the edge prev_p-p0 and to the circle at p0
+    let t_n = Vec2::new(-t.y, t.x); // normal to t
+
+    // the center of the circle is the intersection of lines
+    // p0 + alpha * t_n and m + beta * c_n (alpha and beta are real parameters)
+    // this leads to an equation
+    // mat2x2(tx, -cx, ty, -cy) * vec2(alpha, beta) = vec2(mx - p0x, my - p0y)
+    // where t_n = [tx, ty], c_n = [cx, cy], m = (mx, my) and p0 = (p0x, p0y)
+    // we solve this equation for alpha (and beta)
+
+    let det_den = t_n.x * (-c_n.y) - (-c_n.x) * t_n.y;
+    if det_den.abs() < constants::EPS {
+        // vectors are parallel, so the intersection point doesn't exist
+        return (m, c.length() / 2.0);
+    }
+    let det_num = (m.x - p0.x) * (-c_n.y) - (-c_n.x) * (m.y - p0.y);
+    let alpha = det_num / det_den;
+
+    let s = p0 + alpha * t_n;
+    let r = p0.distance(s);
+
+    (s, r)
+}
+
+// center, radius and angular span of a circular arc
+// (a positive sweep goes clockwise on the screen, since the y axis points down)
+#[derive(Clone, Copy, Debug)]
+pub struct ArcData {
+    pub center: Pos2,
+    pub radius: f64,
+    pub start_angle: f64,
+    pub sweep: f64,
+}
+
+impl ArcData {
+    pub fn point_at(&self, alpha: f64) -> Pos2 {
+        self.center + self.radius * Vec2::angled(alpha)
+    }
+
+    pub fn midpoint(&self) -> Pos2 {
+        self.point_at(self.start_angle + self.sweep / 2.0)
+    }
+}
+
+fn angle_of(s: Pos2, p: Pos2) -> f64 {
+    (p.y - s.y).atan2(p.x - s.x)
+}
+
+// angle swept when going from alpha0 to alpha1 in the given direction
+fn directed_sweep(alpha0: f64, alpha1: f64, clockwise: bool) -> f64 {
+    let d = (alpha1 - alpha0).rem_euclid(2.0 * consts::PI);
+    if clockwise { d } else { d - 2.0 * consts::PI }
+}
+
+// the arc starting at p0 and ending at p1 that leaves p0 in the direction of the tangent
+fn tangent_arc(p0: Pos2, p1: Pos2, s: Pos2, r: f64, tangent: Vec2) -> ArcData {
+    let alpha0 = angle_of(s, p0);
+    // the direction in which the angle grows at p0
+    let clockwise = Vec2::angled(alpha0 + consts::FRAC_PI_2).dot(tangent) >= 0.0;
+
+    ArcData {
+        center: s,
+        radius: r,
+        start_angle: alpha0,
+        sweep: directed_sweep(alpha0, angle_of(s, p1), clockwise),
+    }
+}
+
+// the arc from p0 to p1 with the given radius (at least half of the chord) and direction
+pub fn arc_with_radius(p0: Pos2, p1: Pos2, r: f64, large: bool, clockwise: bool) -> ArcData {
+    let m = midpoint(p0, p1);
+    let half_chord = p0.distance(p1) / 2.0;
+    let r = r.max(half_chord);
+    let n = (p1 - p0).normalized().rot90();
+    let d = (r * r - half_chord * half_chord).sqrt();
+
+    [m + d * n, m - d * n]
+        .into_iter()
+        .map(|s| {
+            let alpha0 = angle_of(s, p0);
+            ArcData {
+                center: s,
+                radius: r,
+                start_angle: alpha0,
+                sweep: directed_sweep(alpha0, angle_of(s, p1), clockwise),
+            }
+        })
+        .find(|arc| (arc.sweep.abs() > consts::PI) == large)
+        .unwrap_or(ArcData {
+            center: m,
+            radius: r,
+            start_angle: angle_of(m, p0),
+            sweep: if clockwise { consts::PI } else { -consts::PI },
+        })
+}
+
+// returns (radius, large, clockwise) of the arc from p0 to p1 going through q
+// projected onto the bisector of the chord, or None if the arc would be a straight line
+pub fn arc_through_point(p0: Pos2, p1: Pos2, q: Pos2) -> Option<(f64, bool, bool)> {
+    let m = midpoint(p0, p1);
+    let half_chord = p0.distance(p1) / 2.0;
+    let n = (p1 - p0).normalized().rot90();
+    // signed distance of the arc's midpoint from the chord
+    let sagitta = (q - m).dot(n);
+    if sagitta.abs() < constants::DIST_EPS {
+        return None;
+    }
+    let r = (half_chord * half_chord + sagitta * sagitta) / (2.0 * sagitta.abs());
+    let s = m + n * (sagitta - r * sagitta.signum());
+    let alpha0 = angle_of(s, p0);
+    let clockwise = directed_sweep(alpha0, angle_of(s, m + sagitta * n), true)
+        < directed_sweep(alpha0, angle_of(s, p1), true);
+
+    Some((r, sagitta.abs() > half_chord, clockwise))
+}
+
+// returns parameters of the arc from edge prev-v0 to v1-next
+// the positions of prev and next matter when v0 or v1 have G1 continuity
+pub fn circular_arc_data(v0: Vertex, v1: Vertex, prev: Vertex, next: Vertex) -> ArcData {
+    use VertexConstraint as VC;
+
+    let arc = v0.arc.unwrap_or_default();
+    if let Some(r) = arc.radius {
+        return arc_with_radius(v0.p, v1.p, r, arc.large, arc.clockwise);
+    }
+    match (v0.vertex_c, v1.vertex_c) {
+        (VC::G1, VC::G0) => {
+            let (s, r) = circular_arc_data_with_g1(v0.p, v1.p, prev.p);
+            tangent_arc(v0.p, v1.p, s, r, v0.p - prev.p)
+        }
+        (VC::G0, VC::G1) => {
+            let (s, r) = circular_arc_data_with_g1(v1.p, v0.p, next.p);
+            let reversed = tangent_arc(v1.p, v0.p, s, r, v1.p - next.p);
+            ArcData {
+                start_angle: reversed.start_angle + reversed.sweep,
+                sweep: -reversed.sweep,
+                ..reversed
+            }
+        }
+        // a semicircle, also if we somehow manage to have invalid constraints
+        _ => arc_with_radius(v0.p, v1.p, 0.0, false, arc.clockwise),
+    }
+}
+
+// returns points of a polyline that is at most `tolerance` away from the arc
+pub fn arc_points(arc: ArcData, tolerance: f64) -> Vec<Pos2> {
+    // a chord spanning the angle d_alpha is r * (1 - cos(d_alpha / 2)) away from the arc
+    let d_alpha = if tolerance < arc.radius {
+        2.0 * (1.0 - tolerance / arc.radius).acos()
+    } else {
+        consts::FRAC_PI_2
+    };
+    let n = (arc.sweep.abs() / d_alpha).ceil().max(1.0) as usize;
+
+    (0..=n)
+        .map(|k| arc.point_at(arc.start_angle + arc.sweep * k as f64 / n as f64))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(p: Pos2, q: Pos2) {
+        assert!(p.distance(q) < constants::EPS, "{p:?} != {q:?}");
+    }
+
+    #[test]
+    fn keep_g1_puts_the_free_point_opposite_the_control_point() {
+        let (fixed, free, control) = (
+            Pos2::new(10.0, 10.0),
+            Pos2::new(10.0, 60.0),
+            Pos2::new(40.0, 50.0),
+        );
+        let moved = keep_g1(fixed, free, control);
+        assert!(check_g1(moved, fixed, control));
+        // the length of the edge is kept
+        assert!((moved.distance(fixed) - free.distance(fixed)).abs() < constants::EPS);
+        assert_near(moved, Pos2::new(-20.0, -30.0));
+    }
+
+    #[test]
+    fn keep_g1_keeps_points_which_already_satisfy_it() {
+        let (fixed, free, control) = (
+            Pos2::new(0.0, 0.0),
+            Pos2::new(-30.0, 0.0),
+            Pos2::new(5.0, 0.0),
+        );
+        assert!(check_g1(free, fixed, control));
+        assert_near(keep_g1(fixed, free, control), free);
+    }
+
+    #[test]
+    fn enforce_g1_projects_the_control_point_onto_the_edge() {
+        let (p0, p1) = (Pos2::new(0.0, 0.0), Pos2::new(30.0, 0.0));
+        let control = enforce_g1(p0, p1, Pos2::new(50.0, 25.0));
+        assert!(check_g1(p0, p1, control));
+        assert_near(control, Pos2::new(50.0, 0.0));
+    }
+
+    #[test]
+    fn enforce_c1_continues_the_edge_at_a_third_of_its_length() {
+        let (p0, p1) = (Pos2::new(0.0, 0.0), Pos2::new(30.0, 60.0));
+        let control = enforce_c1(p0, p1);
+        assert!(check_c1(p0, p1, control));
+        assert_near(control, Pos2::new(40.0, 80.0));
+    }
+
+    #[test]
+    fn keep_c1_inverts_enforce_c1() {
+        let (fixed, control) = (Pos2::new(30.0, 60.0), Pos2::new(40.0, 80.0));
+        let free = keep_c1(fixed, control);
+        assert!(check_c1(free, fixed, control));
+        assert_near(enforce_c1(free, fixed), control);
+    }
+
+    #[test]
+    fn check_c1_rejects_wrong_handle_lengths() {
+        let (p0, p1) = (Pos2::new(0.0, 0.0), Pos2::new(30.0, 0.0));
+        assert!(check_g1(p0, p1, Pos2::new(60.0, 0.0)));
+        assert!(!check_c1(p0, p1, Pos2::new(60.0, 0.0)));
+    }
+
+    #[test]
+    fn circular_arc_with_g1_is_tangent_to_the_previous_edge() {
+        let (prev_p, p0, p1) = (
+            Pos2::new(-50.0, 0.0),
+            Pos2::new(0.0, 0.0),
+            Pos2::new(40.0, 40.0),
+        );
+        let (s, r) = circular_arc_data_with_g1(p0, p1, prev_p);
+        // both ends lie on the circle
+        assert!((s.distance(p0) - r).abs() < constants::EPS);
+        assert!((s.distance(p1) - r).abs() < constants::EPS);
+        // the radius at p0 is perpendicular to the edge coming into it
+        assert!((s - p0).dot(p0 - prev_p).abs() < constants::EPS);
+        assert_near(s, Pos2::new(0.0, 40.0));
+    }
+
+    #[test]
+    fn circular_arc_with_g1_along_the_chord_is_a_half_circle() {
+        let (prev_p, p0, p1) = (
+            Pos2::new(-10.0, 0.0),
+            Pos2::new(0.0, 0.0),
+            Pos2::new(20.0, 0.0),
+        );
+        let (s, r) = circular_arc_data_with_g1(p0, p1, prev_p);
+        assert_near(s, Pos2::new(10.0, 0.0));
+        assert!((r - 10.0).abs() < constants::EPS);
+    }
+
+    // consecutive points are neighbors (including diagonal ones)
+    fn assert_connected(points: &[Pos2]) {
+        for pair in points.windows(2) {
+            let d = pair[1] - pair[0];
+            assert!(d.x.abs() <= 1.0 && d.y.abs() <= 1.0 && d != Vec2::ZERO);
+        }
+    }
+
+    #[test]
+    fn bresenham_covers_a_horizontal_line() {
+        let points = bresenham_points(Pos2::new(2.0, 5.0), Pos2::new(7.0, 5.0));
+        let expected: Vec<Pos2> = (2..=7).map(|x| Pos2::new(x as f64, 5.0)).collect();
+        assert_eq!(points, expected);
+    }
+
+    #[test]
+    fn bresenham_covers_a_diagonal_line() {
+        let points = bresenham_points(Pos2::new(0.0, 0.0), Pos2::new(4.0, 4.0));
+        let expected: Vec<Pos2> = (0..=4).map(|k| Pos2::new(k as f64, k as f64)).collect();
+        assert_eq!(points, expected);
+    }
+
+    #[test]
+    fn bresenham_handles_steep_and_backward_lines() {
+        for (p0, p1) in [
+            (Pos2::new(0.0, 0.0), Pos2::new(3.0, 11.0)),
+            (Pos2::new(9.0, 2.0), Pos2::new(-4.0, 6.0)),
+            (Pos2::new(5.0, 12.0), Pos2::new(1.0, -3.0)),
+        ] {
+            let points = bresenham_points(p0, p1);
+            let (dx, dy) = ((p1.x - p0.x).abs(), (p1.y - p0.y).abs());
+            // one point per step along the longer axis
+            assert_eq!(points.len(), dx.max(dy) as usize + 1);
+            assert!(points.contains(&p0) && points.contains(&p1));
+            let mut sorted = points.clone();
+            if dy > dx {
+                sorted.sort_by(|a, b| a.y.total_cmp(&b.y));
+            } else {
+                sorted.sort_by(|a, b| a.x.total_cmp(&b.x));
+            }
+            assert_connected(&sorted);
+        }
+    }
+
+    #[test]
+    fn integrate_is_exact_for_polynomials_up_to_degree_9() {
+        // the integral of x^8 + x^9 over [0, 2] is 2^9 / 9 + 2^10 / 10
+        let integral = integrate(|x| x.powi(8) + x.powi(9), 0.0, 2.0);
+        assert!((integral - (512.0 / 9.0 + 102.4)).abs() < 1e-12);
+    }
+}
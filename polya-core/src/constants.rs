@@ -0,0 +1,52 @@
+pub const SIZE_VERTEX: f64 = 4.0;
+pub const SIZE_CONTROL_VERTEX: f64 = 6.0;
+pub const SIZE_ROTATE_HANDLE_OFFSET: f64 = 25.0;
+pub const SIZE_HITRADIUS: f64 = 2.0;
+pub const SIZE_MIN_PRIMITIVE: f64 = 5.0;
+pub const SIZE_MIN_EDGE_LENGTH: f64 = 1.0;
+
+pub const EPS: f64 = 0.01;
+pub const DOT_EPS: f64 = 0.001;
+pub const DIST_EPS: f64 = 0.1;
+// how far off a constraint can be and still hold: positions and lengths in model units,
+// directions as the cosine or sine of the angle they're off by, proportions as a ratio
+pub const CONSTRAINT_DIST_TOLERANCE: f64 = 1e-6;
+pub const CONSTRAINT_DIR_TOLERANCE: f64 = 1e-9;
+pub const MAX_FLATTENING_DEPTH: u8 = 16;
+pub const ARC_LENGTH_SUBDIVISIONS: usize = 8;
+pub const BEZIER_PROJECTION_SAMPLES: usize = 64;
+pub const BEZIER_PROJECTION_ITERS: u8 = 32;
+
+pub const INTERSECTION_TOLERANCE: f64 = 0.05;
+pub const INTERSECTION_MERGE_DIST: f64 = 1.0;
+pub const MAX_INTERSECTION_DEPTH: u8 = 24;
+pub const MAX_INTERSECTION_STEPS: usize = 10000;
+
+pub const BOOLEAN_EPS: f64 = 0.01;
+
+pub const MAX_OFFSET_DEPTH: u8 = 8;
+// longest allowed miter as a multiple of the offset distance, longer ones are beveled
+pub const MITER_LIMIT: f64 = 4.0;
+// distance from an offset piece at which the winding number is checked
+pub const OFFSET_PROBE_DIST: f64 = 0.5;
+
+pub const MAX_FIT_ITERS: u8 = 4;
+// the error below which reparameterization is tried before splitting, relative to the tolerance
+pub const FIT_REPARAMETERIZE_FACTOR: f64 = 4.0;
+
+pub const MAX_DELAUNAY_PASSES: usize = 256;
+
+pub const ROTATION_SNAP: f64 = 15.0;
+pub const MIN_SCALE: f64 = 0.01;
+
+pub const MAX_RESOLVING_ITERS: u8 = 64;
+
+// how dimension annotations are laid out, in model units
+pub const SIZE_DIMENSION_OFFSET: f64 = 20.0;
+pub const SIZE_DIMENSION_GAP: f64 = 3.0;
+pub const SIZE_DIMENSION_OVERSHOOT: f64 = 4.0;
+pub const SIZE_DIMENSION_ARROW_LENGTH: f64 = 8.0;
+pub const SIZE_DIMENSION_ARROW_WIDTH: f64 = 5.0;
+pub const SIZE_DIMENSION_ANGLE_RADIUS: f64 = 25.0;
+pub const SIZE_DIMENSION_TEXT_OFFSET: f64 = 10.0;
+pub const SIZE_DIMENSION_FONT: f64 = 12.0;
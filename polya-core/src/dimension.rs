@@ -0,0 +1,154 @@
+use std::f64::consts;
+
+use crate::{
+    calc::{self, ArcData},
+    constants,
+    math::{Pos2, Vec2},
+    polygon::Polygon,
+    properties::Properties,
+    segment::Segment,
+    units::Units,
+};
+
+// a dimension laid out in model units, ready to be drawn or exported
+#[derive(Clone, Debug, PartialEq)]
+pub struct Annotation {
+    // extension lines, dimension lines and arcs as polylines
+    pub lines: Vec<Vec<Pos2>>,
+    // filled triangles, the tip first
+    pub arrows: Vec<[Pos2; 3]>,
+    pub text: String,
+    // where the center of the text goes
+    pub text_pos: Pos2,
+}
+
+fn arrow(tip: Pos2, dir: Vec2) -> [Pos2; 3] {
+    let base = tip - constants::SIZE_DIMENSION_ARROW_LENGTH * dir;
+    let side = (constants::SIZE_DIMENSION_ARROW_WIDTH / 2.0) * dir.rot90();
+
+    [tip, base + side, base - side]
+}
+
+// the distance between the ends of an edge, measured along it and drawn on its outer side
+// (`outward` is 1 if the outer side is to the left when going along the edge, -1 otherwise)
+fn length_dimension(
+    segment: &Segment,
+    outward: f64,
+    units: Units,
+    tolerance: f64,
+) -> Option<Annotation> {
+    let (p0, p1) = (segment.start(), segment.end());
+    let len = p0.distance(p1);
+    if len < constants::EPS {
+        return None;
+    }
+    let dir = (p1 - p0) / len;
+    let n = outward * dir.rot90();
+    // curved edges can bulge out past the line between their ends
+    let bulge = segment
+        .points(tolerance)
+        .iter()
+        .map(|&p| (p - p0).dot(n))
+        .fold(0.0, f64::max);
+    let offset = bulge + constants::SIZE_DIMENSION_OFFSET;
+    let (q0, q1) = (p0 + offset * n, p1 + offset * n);
+    let overshoot = constants::SIZE_DIMENSION_OVERSHOOT * n;
+    let gap = constants::SIZE_DIMENSION_GAP * n;
+
+    Some(Annotation {
+        lines: vec![
+            vec![p0 + gap, q0 + overshoot],
+            vec![p1 + gap, q1 + overshoot],
+            vec![q0, q1],
+        ],
+        arrows: vec![arrow(q0, -dir), arrow(q1, dir)],
+        text: units.format(len),
+        text_pos: calc::midpoint(q0, q1) + constants::SIZE_DIMENSION_TEXT_OFFSET * n,
+    })
+}
+
+// the inner angle between two consecutive edges, measured between their tangents
+// (`orientation` is the sign of the polygon's signed area)
+fn angle_dimension(
+    incoming: &Segment,
+    outgoing: &Segment,
+    orientation: f64,
+    tolerance: f64,
+) -> Option<Annotation> {
+    let (t_in, t_out) = (incoming.tangent(1.0), outgoing.tangent(0.0));
+    if t_in.length() < 0.5 || t_out.length() < 0.5 {
+        return None;
+    }
+    // positive turns go clockwise on the screen, like the outline of a positive area
+    let turn = calc::cross(t_in, t_out).atan2(t_in.dot(t_out));
+    let inner = consts::PI - orientation * turn;
+    let arc = ArcData {
+        center: outgoing.start(),
+        radius: constants::SIZE_DIMENSION_ANGLE_RADIUS,
+        start_angle: t_out.angle(),
+        sweep: orientation * inner,
+    };
+    let segment = Segment::Arc(arc);
+
+    Some(Annotation {
+        lines: vec![segment.points(tolerance)],
+        arrows: vec![
+            arrow(segment.start(), -segment.tangent(0.0)),
+            arrow(segment.end(), segment.tangent(1.0)),
+        ],
+        text: format!("{:.1}°", inner.to_degrees()),
+        text_pos: arc.center
+            + (arc.radius + constants::SIZE_DIMENSION_TEXT_OFFSET)
+                * Vec2::angled(arc.start_angle + arc.sweep / 2.0),
+    })
+}
+
+// a leader from the center of an arc to its midpoint
+fn radius_dimension(arc: &ArcData, units: Units) -> Annotation {
+    let mid = arc.midpoint();
+    let dir = (mid - arc.center).normalized();
+
+    Annotation {
+        lines: vec![vec![arc.center, mid]],
+        arrows: vec![arrow(mid, dir)],
+        text: format!("R {}", units.format(arc.radius)),
+        text_pos: calc::midpoint(arc.center, mid)
+            + constants::SIZE_DIMENSION_TEXT_OFFSET * dir.rot90(),
+    }
+}
+
+// all dimensions turned on for the edges of the polygon
+pub fn annotations(polygon: &Polygon, units: Units, tolerance: f64) -> Vec<Annotation> {
+    let n = polygon.vertices.len();
+    if n < 2 {
+        return Vec::new();
+    }
+    let orientation = Properties::of(polygon).signed_area.signum();
+    let segments: Vec<Segment> = polygon.segments().collect();
+    let mut annotations = Vec::new();
+    for (e_i, segment) in segments.iter().enumerate() {
+        let dims = polygon.vertices[e_i].dims;
+        if dims.length
+            && let Some(annotation) = length_dimension(segment, orientation, units, tolerance)
+        {
+            annotations.push(annotation);
+        }
+        if dims.angle
+            && let Some(annotation) = angle_dimension(
+                &segments[(e_i + n - 1) % n],
+                segment,
+                orientation,
+                tolerance,
+            )
+        {
+            annotations.push(annotation);
+        }
+        if dims.radius
+            && let Segment::Arc(arc) = segment
+        {
+            annotations.push(radius_dimension(arc, units));
+        }
+    }
+
+    annotations
+}
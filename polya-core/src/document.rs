@@ -0,0 +1,501 @@
+use std::fmt;
+
+use crate::{
+    constants,
+    dimension::{self, Annotation},
+    math::{Pos2, Rect, Vec2},
+    polygon::Polygon,
+    segment::Segment,
+    triangulation::{self, Mesh, TriangulationError},
+    units::{Unit, Units},
+    vertex::{CircleArc, CubicBezier, EdgeConstraint, Vertex, VertexConstraint},
+};
+
+// a plain text file with the units of the document and one shape after another, for example:
+//
+// units mm 3.7795
+// shape
+// v 400 200 bezier 390 210 360 240 joint g1
+// v 450 150 edge diagonal-up
+// v 500 100 arc auto small cw dim radius
+// hole
+// v 500 250 edge length 100 dim length dim angle
+// ...
+//
+// each vertex line gives the vertex and, optionally, what the edge starting
+// at it is (a Bézier curve or an arc), its constraint, the continuity at the vertex
+// and which of its dimensions are shown,
+// everything is in model units, the `units` line only says how they map to real-world ones
+// and can be left out for documents measured in points
+
+#[derive(Debug, Default)]
+pub struct Document {
+    pub shapes: Vec<Polygon>,
+    pub units: Units,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+// there's nothing to draw in a document without shapes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EmptyDocument;
+
+impl fmt::Display for EmptyDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the document has no shapes")
+    }
+}
+
+fn vertex_line(v: &Vertex) -> String {
+    let mut line = format!("v {} {}", v.p.x, v.p.y);
+    if let Some(bezier) = v.bezier {
+        let [c0, c1] = bezier.control;
+        line += &format!(" bezier {} {} {} {}", c0.x, c0.y, c1.x, c1.y);
+    }
+    if let Some(arc) = v.arc {
+        let radius = arc.radius.map_or("auto".to_string(), |r| r.to_string());
+        let large = if arc.large { "large" } else { "small" };
+        let clockwise = if arc.clockwise { "cw" } else { "ccw" };
+        line += &format!(" arc {radius} {large} {clockwise}");
+    }
+    match v.edge_c {
+        Some(EdgeConstraint::Vertical) => line += " edge vertical",
+        Some(EdgeConstraint::Horizontal) => line += " edge horizontal",
+        Some(EdgeConstraint::DiagonalUp) => line += " edge diagonal-up",
+        Some(EdgeConstraint::DiagonalDown) => line += " edge diagonal-down",
+        Some(EdgeConstraint::FixedLength(len)) => line += &format!(" edge length {len}"),
+        None => (),
+    }
+    let joint = match v.vertex_c {
+        VertexConstraint::G0 => "g0",
+        VertexConstraint::G1 => "g1",
+        VertexConstraint::C1 => "c1",
+        VertexConstraint::G2 => "g2",
+        VertexConstraint::C2 => "c2",
+    };
+
+    line += " joint ";
+    line += joint;
+    for (shown, kind) in [
+        (v.dims.length, "length"),
+        (v.dims.angle, "angle"),
+        (v.dims.radius, "radius"),
+    ] {
+        if shown {
+            line += " dim ";
+            line += kind;
+        }
+    }
+
+    line
+}
+
+pub fn to_text(doc: &Document) -> String {
+    let mut text = String::from("# polya document\n");
+    text += &format!("units {} {}\n", doc.units.unit, doc.units.scale);
+    for polygon in doc.shapes.iter() {
+        text += if polygon.hole { "hole\n" } else { "shape\n" };
+        for v in polygon.vertices.iter() {
+            text += &vertex_line(v);
+            text += "\n";
+        }
+    }
+
+    text
+}
+
+// reads the words of a vertex line one by one
+struct Words<'a> {
+    words: std::str::SplitWhitespace<'a>,
+    line: usize,
+}
+
+impl<'a> Words<'a> {
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn word(&mut self) -> Result<&'a str, ParseError> {
+        let line = self.line;
+        self.words.next().ok_or(ParseError {
+            line,
+            message: "unexpected end of line".to_string(),
+        })
+    }
+
+    fn number(&mut self) -> Result<f64, ParseError> {
+        let word = self.word()?;
+        match word.parse::<f64>() {
+            Ok(x) if x.is_finite() => Ok(x),
+            _ => Err(self.error(format!("expected a number, found `{word}`"))),
+        }
+    }
+
+    fn point(&mut self) -> Result<Pos2, ParseError> {
+        Ok(Pos2::new(self.number()?, self.number()?))
+    }
+}
+
+fn parse_vertex(words: &mut Words) -> Result<Vertex, ParseError> {
+    let mut v = Vertex::new(words.point()?);
+    while let Some(key) = words.words.next() {
+        match key {
+            "bezier" => v.bezier = Some(CubicBezier::new([words.point()?, words.point()?])),
+            "arc" => {
+                let radius = match words.word()? {
+                    "auto" => None,
+                    r => Some(
+                        r.parse::<f64>()
+                            .ok()
+                            .filter(|r| r.is_finite() && *r > 0.0)
+                            .ok_or_else(|| words.error(format!("invalid radius `{r}`")))?,
+                    ),
+                };
+                let large = match words.word()? {
+                    "small" => false,
+                    "large" => true,
+                    other => {
+                        return Err(words.error(format!("expected small/large, found `{other}`")));
+                    }
+                };
+                let clockwise = match words.word()? {
+                    "cw" => true,
+                    "ccw" => false,
+                    other => return Err(words.error(format!("expected cw/ccw, found `{other}`"))),
+                };
+                v.arc = Some(CircleArc::new(radius, large, clockwise));
+            }
+            "edge" => {
+                v.edge_c = Some(match words.word()? {
+                    "vertical" => EdgeConstraint::Vertical,
+                    "horizontal" => EdgeConstraint::Horizontal,
+                    "diagonal-up" => EdgeConstraint::DiagonalUp,
+                    "diagonal-down" => EdgeConstraint::DiagonalDown,
+                    "length" => {
+                        let len = words.number()?;
+                        if len < constants::SIZE_MIN_EDGE_LENGTH {
+                            return Err(words.error(format!(
+                                "an edge can't be shorter than {}",
+                                constants::SIZE_MIN_EDGE_LENGTH
+                            )));
+                        }
+                        EdgeConstraint::FixedLength(len)
+                    }
+                    other => return Err(words.error(format!("unknown edge constraint `{other}`"))),
+                });
+            }
+            "joint" => {
+                v.vertex_c = match words.word()? {
+                    "g0" => VertexConstraint::G0,
+                    "g1" => VertexConstraint::G1,
+                    "c1" => VertexConstraint::C1,
+                    "g2" => VertexConstraint::G2,
+                    "c2" => VertexConstraint::C2,
+                    other => return Err(words.error(format!("unknown continuity `{other}`"))),
+                };
+            }
+            "dim" => match words.word()? {
+                "length" => v.dims.length = true,
+                "angle" => v.dims.angle = true,
+                "radius" => v.dims.radius = true,
+                other => return Err(words.error(format!("unknown dimension `{other}`"))),
+            },
+            other => return Err(words.error(format!("unknown attribute `{other}`"))),
+        }
+    }
+    if v.bezier.is_some() && v.arc.is_some() {
+        return Err(words.error("an edge can't be both a Bézier curve and an arc"));
+    }
+
+    Ok(v)
+}
+
+fn parse_units(words: &mut Words) -> Result<Units, ParseError> {
+    let name = words.word()?;
+    let unit =
+        Unit::from_name(name).ok_or_else(|| words.error(format!("unknown unit `{name}`")))?;
+    let scale = match words.words.next() {
+        None => unit.default_scale(),
+        Some(s) => s
+            .parse::<f64>()
+            .ok()
+            .filter(|s| s.is_finite() && *s > 0.0)
+            .ok_or_else(|| words.error(format!("invalid scale `{s}`")))?,
+    };
+    if let Some(other) = words.words.next() {
+        return Err(words.error(format!("unexpected `{other}`")));
+    }
+
+    Ok(Units { unit, scale })
+}
+
+pub fn from_text(text: &str) -> Result<Document, ParseError> {
+    let mut units = Units::default();
+    let mut shapes: Vec<(usize, Polygon)> = Vec::new();
+    for (line_i, line) in text.lines().enumerate() {
+        let line_no = line_i + 1;
+        let line = line.split('#').next().unwrap_or_default();
+        let mut words = Words {
+            words: line.split_whitespace(),
+            line: line_no,
+        };
+        match words.words.next() {
+            None => (),
+            Some("units") => units = parse_units(&mut words)?,
+            Some("shape") => shapes.push((line_no, Polygon::new(Vec::new()))),
+            Some("hole") => shapes.push((line_no, Polygon::from_points(&[], true))),
+            Some("v") => {
+                let v = parse_vertex(&mut words)?;
+                let Some((_, polygon)) = shapes.last_mut() else {
+                    return Err(words.error("vertex outside of a shape"));
+                };
+                polygon.vertices.push(v);
+            }
+            Some(other) => return Err(words.error(format!("unknown keyword `{other}`"))),
+        }
+    }
+    if let Some((line, _)) = shapes
+        .iter()
+        .find(|(_, polygon)| polygon.vertices.len() < 3)
+    {
+        return Err(ParseError {
+            line: *line,
+            message: "a shape needs at least three vertices".to_string(),
+        });
+    }
+
+    Ok(Document {
+        shapes: shapes.into_iter().map(|(_, polygon)| polygon).collect(),
+        units,
+    })
+}
+
+// the outline as SVG path commands, curves are kept as curves
+fn svg_path(polygon: &Polygon) -> String {
+    let mut path = String::new();
+    for (e_i, segment) in polygon.segments().enumerate() {
+        if e_i == 0 {
+            let p = segment.start();
+            path += &format!("M {} {}", p.x, p.y);
+        }
+        match segment {
+            Segment::Line([_, p1]) => path += &format!(" L {} {}", p1.x, p1.y),
+            Segment::Cubic([_, c0, c1, p1]) => {
+                path += &format!(" C {} {} {} {} {} {}", c0.x, c0.y, c1.x, c1.y, p1.x, p1.y);
+            }
+            // in SVG the sweep flag is set for arcs going clockwise on the screen
+            Segment::Arc(arc) => {
+                let p1 = segment.end();
+                let large = arc.sweep.abs() > std::f64::consts::PI;
+                path += &format!(
+                    " A {r} {r} 0 {} {} {} {}",
+                    large as u8,
+                    (arc.sweep > 0.0) as u8,
+                    p1.x,
+                    p1.y,
+                    r = arc.radius,
+                );
+            }
+        }
+    }
+
+    path + " Z"
+}
+
+fn svg_points(points: &[Pos2]) -> String {
+    points
+        .iter()
+        .map(|p| format!("{},{}", p.x, p.y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn svg_annotation(annotation: &Annotation) -> String {
+    let mut svg = String::new();
+    for line in annotation.lines.iter() {
+        svg += &format!("<polyline points=\"{}\"/>\n", svg_points(line));
+    }
+    for arrow in annotation.arrows.iter() {
+        svg += &format!(
+            "<polygon points=\"{}\" fill=\"black\"/>\n",
+            svg_points(arrow)
+        );
+    }
+    // the text is plain apart from the few characters XML reserves
+    let text = annotation
+        .text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    svg += &format!(
+        "<text x=\"{}\" y=\"{}\" fill=\"black\" stroke=\"none\">{text}</text>\n",
+        annotation.text_pos.x, annotation.text_pos.y
+    );
+
+    svg
+}
+
+// all shapes as a single path filled with the even-odd rule, so that holes stay empty,
+// followed by their dimensions; the drawing keeps model units inside a group
+// scaled to the document's units, which the view box and the size are given in
+pub fn to_svg(doc: &Document, tolerance: f64) -> Result<String, EmptyDocument> {
+    if doc.shapes.is_empty() {
+        return Err(EmptyDocument);
+    }
+    let annotations: Vec<Annotation> = doc
+        .shapes
+        .iter()
+        .flat_map(|polygon| dimension::annotations(polygon, doc.units, tolerance))
+        .collect();
+    let shapes_bbox = doc
+        .shapes
+        .iter()
+        .flat_map(|polygon| polygon.segments())
+        .fold(Rect::NOTHING, |bbox, segment| {
+            bbox.union(segment.bounding_box())
+        });
+    // texts are only known by where they're centered, so leave them some room
+    let bbox = annotations.iter().fold(shapes_bbox, |bbox, annotation| {
+        let points: Vec<Pos2> = annotation
+            .lines
+            .iter()
+            .flatten()
+            .chain(annotation.arrows.iter().flatten())
+            .copied()
+            .collect();
+        bbox.union(Rect::from_points(&points))
+            .union(Rect::from_center_size(
+                annotation.text_pos,
+                Vec2::new(6.0, 2.0) * constants::SIZE_DIMENSION_FONT,
+            ))
+    });
+    let path = doc
+        .shapes
+        .iter()
+        .map(svg_path)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let dimensions: String = annotations.iter().map(svg_annotation).collect();
+
+    Ok(format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}{unit}\" height=\"{}{unit}\" \
+         viewBox=\"{} {} {} {}\">\n\
+         <g transform=\"scale({})\">\n\
+         <path d=\"{path}\" fill=\"lightgray\" fill-rule=\"evenodd\" stroke=\"black\"/>\n\
+         <g fill=\"none\" stroke=\"black\" stroke-width=\"0.5\" font-size=\"{}\" \
+         text-anchor=\"middle\" dominant-baseline=\"middle\">\n\
+         {dimensions}</g>\n\
+         </g>\n\
+         </svg>\n",
+        doc.units.to_unit(bbox.width()),
+        doc.units.to_unit(bbox.height()),
+        doc.units.to_unit(bbox.min.x),
+        doc.units.to_unit(bbox.min.y),
+        doc.units.to_unit(bbox.width()),
+        doc.units.to_unit(bbox.height()),
+        doc.units.to_unit(1.0),
+        constants::SIZE_DIMENSION_FONT,
+        unit = doc.units.unit,
+    ))
+}
+
+// triangulations of all shapes that aren't holes, with the holes inside them cut out
+pub fn to_mesh(doc: &Document, tolerance: f64) -> Result<Mesh, TriangulationError> {
+    let mut mesh = Mesh::default();
+    for polygon in doc.shapes.iter().filter(|polygon| !polygon.hole) {
+        let region = polygon.region(doc.shapes.iter(), tolerance);
+        let part = triangulation::triangulate(&region[0], &region[1..], true)?;
+        let offset = mesh.vertices.len();
+        mesh.vertices.extend(part.vertices);
+        mesh.triangles
+            .extend(part.triangles.into_iter().map(|t| t.map(|i| i + offset)));
+    }
+
+    Ok(mesh)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Document {
+        let mut shape = Polygon::from_points(
+            &[
+                Pos2::new(0.0, 0.0),
+                Pos2::new(100.0, 0.0),
+                Pos2::new(100.0, 100.0),
+                Pos2::new(0.0, 100.0),
+            ],
+            false,
+        );
+        shape.vertices[0].bezier = Some(CubicBezier::new([
+            Pos2::new(30.5, -40.25),
+            Pos2::new(70.0, -1.0 / 3.0),
+        ]));
+        shape.vertices[0].vertex_c = VertexConstraint::G1;
+        shape.vertices[1].edge_c = Some(EdgeConstraint::Vertical);
+        shape.vertices[1].dims.angle = true;
+        shape.vertices[2].arc = Some(CircleArc::new(Some(75.0), true, false));
+        shape.vertices[2].vertex_c = VertexConstraint::G0;
+        shape.vertices[2].dims.radius = true;
+        shape.vertices[3].edge_c = Some(EdgeConstraint::FixedLength(100.0));
+        shape.vertices[3].dims.length = true;
+        let hole = Polygon::from_points(
+            &[
+                Pos2::new(40.0, 40.0),
+                Pos2::new(60.0, 40.0),
+                Pos2::new(50.0, 60.0),
+            ],
+            true,
+        );
+
+        Document {
+            shapes: vec![shape, hole],
+            units: Units {
+                unit: Unit::default(),
+                scale: 3.7795,
+            },
+        }
+    }
+
+    #[test]
+    fn from_text_reads_what_to_text_writes() {
+        let doc = sample();
+        let parsed = from_text(&to_text(&doc)).unwrap();
+        assert_eq!(parsed.units.unit, doc.units.unit);
+        assert_eq!(parsed.units.scale, doc.units.scale);
+        assert_eq!(parsed.shapes.len(), doc.shapes.len());
+        for (a, b) in parsed.shapes.iter().zip(doc.shapes.iter()) {
+            assert_eq!(a.hole, b.hole);
+            assert_eq!(a.vertices, b.vertices);
+        }
+    }
+
+    #[test]
+    fn from_text_rejects_too_short_fixed_lengths() {
+        for len in ["0", "-5", "0.5"] {
+            let text = format!("shape\nv 0 0 edge length {len}\nv 10 0\nv 0 10\n");
+            let error = from_text(&text).unwrap_err();
+            assert_eq!(error.line, 2, "{len}");
+        }
+    }
+
+    #[test]
+    fn to_svg_needs_a_shape() {
+        assert_eq!(to_svg(&Document::default(), 0.1), Err(EmptyDocument));
+        assert!(to_svg(&sample(), 0.1).is_ok());
+    }
+}
@@ -0,0 +1,196 @@
+use crate::{
+    calc, constants,
+    math::{Pos2, Vec2},
+};
+
+// a fitted curve: index of the point it starts at and its control polygon
+pub type Piece = (usize, [Pos2; 4]);
+
+// parameters of the points proportional to the distance along the polyline
+fn chord_length_parameters(points: &[Pos2]) -> Vec<f64> {
+    let mut u = vec![0.0];
+    for pair in points.windows(2) {
+        u.push(u[u.len() - 1] + pair[0].distance(pair[1]));
+    }
+    let total = u[u.len() - 1].max(constants::EPS);
+
+    u.into_iter().map(|x| x / total).collect()
+}
+
+// the Bézier curve with the given end tangents whose handle lengths
+// minimize the squared distances to the points at the given parameters
+fn least_squares_bezier(points: &[Pos2], u: &[f64], t0: Vec2, t1: Vec2) -> [Pos2; 4] {
+    let (p0, p3) = (points[0], points[points.len() - 1]);
+    let (mut c00, mut c01, mut c11, mut x0, mut x1) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for (&p, &t) in points.iter().zip(u) {
+        let s = 1.0 - t;
+        let (b0, b1, b2, b3) = (s * s * s, 3.0 * s * s * t, 3.0 * s * t * t, t * t * t);
+        let (a0, a1) = (t0 * b1, t1 * b2);
+        c00 += a0.dot(a0);
+        c01 += a0.dot(a1);
+        c11 += a1.dot(a1);
+        let rest = p - (p0.to_vec2() * (b0 + b1) + p3.to_vec2() * (b2 + b3));
+        x0 += a0.dot(rest.to_vec2());
+        x1 += a1.dot(rest.to_vec2());
+    }
+    let det = c00 * c11 - c01 * c01;
+    let (alpha0, alpha1) = if det.abs() > constants::DOT_EPS {
+        ((x0 * c11 - x1 * c01) / det, (c00 * x1 - c01 * x0) / det)
+    } else {
+        (0.0, 0.0)
+    };
+    // handles which are too short or point backwards make poor curves,
+    // a third of the chord is a safe guess then
+    let chord = p0.distance(p3);
+    let min_alpha = constants::EPS * chord;
+    let (alpha0, alpha1) = if alpha0 < min_alpha || alpha1 < min_alpha {
+        (chord / 3.0, chord / 3.0)
+    } else {
+        (alpha0, alpha1)
+    };
+
+    [p0, p0 + alpha0 * t0, p3 + alpha1 * t1, p3]
+}
+
+// the largest distance from a point to the curve and the index of that point
+fn max_error(points: &[Pos2], u: &[f64], bezier: [Pos2; 4]) -> (f64, usize) {
+    let [p0, c0, c1, p1] = bezier;
+    (1..points.len() - 1)
+        .map(|i| {
+            let q = calc::cubic_bezier_point(p0, c0, c1, p1, u[i]);
+            (q.distance(points[i]), i)
+        })
+        .fold(
+            (0.0, points.len() / 2),
+            |best, e| {
+                if e.0 > best.0 { e } else { best }
+            },
+        )
+}
+
+// moves the parameters closer to the nearest points of the curve (one Newton step)
+fn reparameterize(points: &[Pos2], u: &mut [f64], bezier: [Pos2; 4]) {
+    let [p0, c0, c1, p1] = bezier;
+    for (p, t) in points.iter().zip(u.iter_mut()) {
+        let q = calc::cubic_bezier_point(p0, c0, c1, p1, *t);
+        let (d1, d2) = calc::cubic_bezier_derivatives(p0, c0, c1, p1, *t);
+        let numerator = (q - *p).dot(d1);
+        let denominator = d1.dot(d1) + (q - *p).dot(d2);
+        if denominator.abs() > constants::DOT_EPS {
+            *t = (*t - numerator / denominator).clamp(0.0, 1.0);
+        }
+    }
+}
+
+fn fit(points: &[Pos2], first: usize, t0: Vec2, t1: Vec2, tolerance: f64, pieces: &mut Vec<Piece>) {
+    if points.len() == 2 {
+        let d = points[0].distance(points[1]) / 3.0;
+        pieces.push((
+            first,
+            [points[0], points[0] + d * t0, points[1] + d * t1, points[1]],
+        ));
+        return;
+    }
+
+    let mut u = chord_length_parameters(points);
+    let mut bezier = least_squares_bezier(points, &u, t0, t1);
+    let (mut error, mut split_i) = max_error(points, &u, bezier);
+    // when the curve is close, better parameters may be enough to get it within tolerance
+    if error > tolerance && error < constants::FIT_REPARAMETERIZE_FACTOR * tolerance {
+        for _ in 0..constants::MAX_FIT_ITERS {
+            reparameterize(points, &mut u, bezier);
+            bezier = least_squares_bezier(points, &u, t0, t1);
+            (error, split_i) = max_error(points, &u, bezier);
+            if error <= tolerance {
+                break;
+            }
+        }
+    }
+    if error <= tolerance {
+        pieces.push((first, bezier));
+        return;
+    }
+
+    // the curves meeting at the point farthest off share its tangent
+    let center = (points[split_i - 1] - points[split_i + 1]).normalized();
+    let center = if center.is_finite() { center } else { -t0 };
+    fit(&points[..=split_i], first, t0, center, tolerance, pieces);
+    fit(
+        &points[split_i..],
+        first + split_i,
+        -center,
+        t1,
+        tolerance,
+        pieces,
+    );
+}
+
+// fits as few Bézier curves as possible to the polyline, keeping every point
+// within the tolerance (Philip J. Schneider, "An Algorithm for Automatically
+// Fitting Digitized Curves"); t0 is the direction in which the curves leave
+// the first point and t1 the one in which they arrive at the last one, reversed,
+// the curves start and end at points of the polyline and meet with common tangents
+pub fn fit_cubics(points: &[Pos2], t0: Vec2, t1: Vec2, tolerance: f64) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    if points.len() >= 2 {
+        fit(points, 0, t0, t1, tolerance, &mut pieces);
+    }
+
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance_to_piece(p: Pos2, [p0, c0, c1, p1]: [Pos2; 4]) -> f64 {
+        let t = calc::nearest_cubic_bezier_parameter(p0, c0, c1, p1, p);
+        calc::cubic_bezier_point(p0, c0, c1, p1, t).distance(p)
+    }
+
+    // every point is within the tolerance from the piece covering it,
+    // and neighboring pieces meet at a point of the polyline
+    fn assert_fits(points: &[Pos2], pieces: &[Piece], tolerance: f64) {
+        for (k, &(first, bezier)) in pieces.iter().enumerate() {
+            let last = pieces.get(k + 1).map_or(points.len() - 1, |&(i, _)| i);
+            assert_eq!(bezier[0], points[first]);
+            assert_eq!(bezier[3], points[last]);
+            for &p in &points[first..=last] {
+                assert!(distance_to_piece(p, bezier) <= tolerance + 1e-9, "{p:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn fit_cubics_recovers_a_single_curve() {
+        let [p0, c0, c1, p1] = [
+            Pos2::new(0.0, 0.0),
+            Pos2::new(30.0, -60.0),
+            Pos2::new(90.0, -60.0),
+            Pos2::new(120.0, 0.0),
+        ];
+        let points: Vec<Pos2> = (0..=20)
+            .map(|k| calc::cubic_bezier_point(p0, c0, c1, p1, k as f64 / 20.0))
+            .collect();
+        let pieces = fit_cubics(&points, (c0 - p0).normalized(), (c1 - p1).normalized(), 1.0);
+        assert_eq!(pieces.len(), 1, "{pieces:?}");
+        assert_fits(&points, &pieces, 1.0);
+    }
+
+    #[test]
+    fn fit_cubics_splits_where_one_curve_isnt_enough() {
+        // a zigzag can't be followed by a single curve
+        let points: Vec<Pos2> = (0..=8)
+            .map(|k| Pos2::new(20.0 * k as f64, if k % 2 == 0 { 0.0 } else { 30.0 }))
+            .collect();
+        let pieces = fit_cubics(&points, Vec2::new(1.0, 0.0), Vec2::new(-1.0, 0.0), 1.0);
+        assert!(pieces.len() > 1);
+        assert_fits(&points, &pieces, 1.0);
+        // the curves meeting at a point share its tangent
+        for pair in pieces.windows(2) {
+            let (t_in, t_out) = (pair[0].1[3] - pair[0].1[2], pair[1].1[1] - pair[1].1[0]);
+            assert!(calc::cross(t_in.normalized(), t_out.normalized()).abs() < 1e-9);
+            assert!(t_in.dot(t_out) > 0.0);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{boolean, calc, constants, math::Pos2, polygon::Polygon, segment::Segment};
+
+#[derive(Clone, Debug)]
+pub struct Convexity {
+    // the hull goes around the same way as the polygon, parts of curved edges
+    // lying on the hull are kept as curves
+    pub hull: Vec<Segment>,
+    pub is_convex: bool,
+    // indices of vertices where the outline turns against its orientation
+    pub reflex_vertices: Vec<usize>,
+}
+
+// indices of the points on the convex hull, in the order of positive signed area
+// (Andrew's monotone chain, collinear points are skipped)
+fn hull_indices(points: &[Pos2]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by(|&a, &b| {
+        points[a]
+            .x
+            .total_cmp(&points[b].x)
+            .then(points[a].y.total_cmp(&points[b].y))
+    });
+    let turn =
+        |a: usize, b: usize, c: usize| calc::cross(points[b] - points[a], points[c] - points[b]);
+    let mut hull: Vec<usize> = Vec::with_capacity(points.len() + 1);
+    for pass in [order.clone(), order.into_iter().rev().collect()] {
+        let start = hull.len();
+        for i in pass {
+            while hull.len() >= start + 2
+                && turn(hull[hull.len() - 2], hull[hull.len() - 1], i) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(i);
+        }
+        // the last point of each half is the first point of the other one
+        hull.pop();
+    }
+
+    hull
+}
+
+impl Convexity {
+    pub fn of(polygon: &Polygon, tolerance: f64) -> Self {
+        let segments: Vec<Segment> = polygon.segments().collect();
+        let n = segments.len();
+        // flattened outline, each point tagged with the segment it starts a piece of
+        let mut points = Vec::new();
+        let mut owners = Vec::new();
+        for (s_i, segment) in segments.iter().enumerate() {
+            let mut pts = segment.points(tolerance);
+            pts.pop();
+            owners.extend(std::iter::repeat_n(s_i, pts.len()));
+            points.extend(pts);
+        }
+        let area = boolean::signed_area(&points);
+
+        let mut hull_i = hull_indices(&points);
+        if area < 0.0 {
+            hull_i.reverse();
+        }
+        let mut hull: Vec<Segment> = Vec::with_capacity(hull_i.len());
+        // for pieces of curves: which segment and the range of its parameter
+        let mut origins: Vec<Option<(usize, f64, f64)>> = Vec::with_capacity(hull_i.len());
+        for k in 0..hull_i.len() {
+            let (i, j) = (hull_i[k], hull_i[(k + 1) % hull_i.len()]);
+            let s_i = owners[i];
+            // neighboring points of a curve that are both on the hull
+            // have the part of the curve between them on the hull too
+            if j == (i + 1) % points.len() && !matches!(segments[s_i], Segment::Line(_)) {
+                let segment = segments[s_i];
+                let t0 = segment.nearest_parameter(points[i]);
+                let t1 = if owners[j] == s_i {
+                    segment.nearest_parameter(points[j])
+                } else {
+                    1.0
+                };
+                // consecutive pieces of the same curve become one
+                if let Some(Some((prev_s_i, prev_t0, prev_t1))) = origins.last().copied()
+                    && prev_s_i == s_i
+                    && (prev_t1 - t0).abs() < constants::DOT_EPS
+                {
+                    hull.pop();
+                    origins.pop();
+                    hull.push(segment.between(prev_t0, t1));
+                    origins.push(Some((s_i, prev_t0, t1)));
+                } else {
+                    hull.push(segment.between(t0, t1));
+                    origins.push(Some((s_i, t0, t1)));
+                }
+            } else {
+                hull.push(Segment::Line([points[i], points[j]]));
+                origins.push(None);
+            }
+        }
+        if let (Some(Some((last_s_i, t0, last_t1))), Some(Some((first_s_i, first_t0, t1)))) =
+            (origins.last().copied(), origins.first().copied())
+            && hull.len() > 1
+            && last_s_i == first_s_i
+            && (last_t1 - first_t0).abs() < constants::DOT_EPS
+            && t0 < t1
+        {
+            hull[0] = segments[first_s_i].between(t0, t1);
+            hull.pop();
+        }
+
+        let reflex_vertices: Vec<usize> = (0..n)
+            .filter(|&v_i| {
+                let (t_in, t_out) = (
+                    segments[(v_i + n - 1) % n].tangent(1.0),
+                    segments[v_i].tangent(0.0),
+                );
+                calc::cross(t_in, t_out) * area.signum() < -constants::DOT_EPS
+            })
+            .collect();
+        // the flattened outline of a convex shape is its own hull
+        let hull_points: Vec<Pos2> = hull_i.iter().map(|&i| points[i]).collect();
+        let hull_area = boolean::signed_area(&hull_points).abs();
+        let is_convex = reflex_vertices.is_empty()
+            && hull_area - area.abs() <= constants::DOT_EPS * hull_area + constants::EPS;
+
+        Self {
+            hull,
+            is_convex,
+            reflex_vertices,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{CurveKind, Primitive};
+
+    fn hull_area(convexity: &Convexity) -> f64 {
+        convexity.hull.iter().map(Segment::area).sum::<f64>().abs()
+    }
+
+    #[test]
+    fn convexity_finds_the_reflex_vertex() {
+        // a square with a notch cut into its top side
+        let polygon = Polygon::from_points(
+            &[
+                Pos2::new(0.0, 0.0),
+                Pos2::new(50.0, 40.0),
+                Pos2::new(100.0, 0.0),
+                Pos2::new(100.0, 100.0),
+                Pos2::new(0.0, 100.0),
+            ],
+            false,
+        );
+        let convexity = Convexity::of(&polygon, 0.1);
+        assert!(!convexity.is_convex);
+        assert_eq!(convexity.reflex_vertices, vec![1]);
+        assert!((hull_area(&convexity) - 10000.0).abs() < 1e-6);
+        assert_eq!(convexity.hull.len(), 4);
+    }
+
+    #[test]
+    fn convexity_keeps_curves_on_the_hull() {
+        let circle = Primitive::Circle(CurveKind::Arc)
+            .build(Pos2::new(0.0, 0.0), Pos2::new(50.0, 0.0))
+            .unwrap();
+        let convexity = Convexity::of(&circle, 0.1);
+        assert!(convexity.is_convex);
+        assert!(convexity.reflex_vertices.is_empty());
+        assert!(
+            convexity
+                .hull
+                .iter()
+                .all(|segment| matches!(segment, Segment::Arc(_)))
+        );
+        assert!((hull_area(&convexity) - std::f64::consts::PI * 2500.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{calc, constants, math::Pos2, polygon::Polygon, segment::Segment};
+
+// finds intersections of two segments by recursively splitting them in half
+// and discarding pairs of pieces whose bounding boxes don't overlap,
+// until both pieces are flat enough to be intersected as straight lines
+fn intersect(a: Segment, b: Segment, depth: u8, budget: &mut usize, points: &mut Vec<Pos2>) {
+    if *budget == 0
+        || !a
+            .bounding_box()
+            .expand(constants::EPS)
+            .intersects(b.bounding_box())
+    {
+        return;
+    }
+    *budget -= 1;
+    let tolerance = constants::INTERSECTION_TOLERANCE;
+    if depth >= constants::MAX_INTERSECTION_DEPTH || (a.is_flat(tolerance) && b.is_flat(tolerance))
+    {
+        if let Some(p) = calc::segments_intersection(a.start(), a.end(), b.start(), b.end()) {
+            points.push(p);
+        }
+        return;
+    }
+    let (a0, a1) = a.split(0.5);
+    let (b0, b1) = b.split(0.5);
+    for (a, b) in [(a0, b0), (a0, b1), (a1, b0), (a1, b1)] {
+        intersect(a, b, depth + 1, budget, points);
+    }
+}
+
+pub fn segment_intersections(a: Segment, b: Segment) -> Vec<Pos2> {
+    let mut points = Vec::new();
+    let mut budget = constants::MAX_INTERSECTION_STEPS;
+    intersect(a, b, 0, &mut budget, &mut points);
+    dedup_points(&mut points);
+
+    points
+}
+
+// removes points that are (nearly) the same as an earlier one
+fn dedup_points(points: &mut Vec<Pos2>) {
+    let mut unique: Vec<Pos2> = Vec::with_capacity(points.len());
+    for &p in points.iter() {
+        if unique
+            .iter()
+            .all(|q| q.distance(p) > constants::INTERSECTION_MERGE_DIST)
+        {
+            unique.push(p);
+        }
+    }
+    *points = unique;
+}
+
+// the point where a cubic Bézier curve crosses itself, if it makes a loop;
+// with B(t) = a * t^3 + b * t^2 + c * t + d, B(s) = B(t) for s != t gives
+// a * (s^2 + s * t + t^2) + b * (s + t) + c = 0, which is linear in s * t
+// for a given s + t, and crossing it with a eliminates s * t
+fn cubic_self_intersection([p0, c0, c1, p1]: [Pos2; 4]) -> Option<Pos2> {
+    let a = (p1 - p0) + 3.0 * (c0 - c1);
+    let b = 3.0 * ((c1 - c0) - (c0 - p0));
+    let c = 3.0 * (c0 - p0);
+    let (ab, aa) = (calc::cross(a, b), a.dot(a));
+    if ab.abs() < constants::DOT_EPS || aa < constants::DOT_EPS {
+        return None;
+    }
+    let sum = -calc::cross(a, c) / ab;
+    let product = sum * sum + a.dot(sum * b + c) / aa;
+    match calc::quadratic_roots(1.0, -sum, product)[..] {
+        [s, t]
+            if s > constants::DOT_EPS
+                && t < 1.0 - constants::DOT_EPS
+                && t - s > constants::DOT_EPS =>
+        {
+            Some(calc::cubic_bezier_point(p0, c0, c1, p1, s))
+        }
+        _ => None,
+    }
+}
+
+// points where edges of the polygon cross each other
+// (not counting the vertices shared by neighboring edges)
+pub fn self_intersections(polygon: &Polygon) -> Vec<Pos2> {
+    let n = polygon.vertices.len();
+    let segments: Vec<_> = polygon.segments().collect();
+    let boxes: Vec<_> = segments.iter().map(|s| s.bounding_box()).collect();
+    let mut points = Vec::new();
+    for i in 0..n {
+        if let Segment::Cubic(p) = segments[i] {
+            points.extend(cubic_self_intersection(p));
+        }
+        for j in (i + 1)..n {
+            if !boxes[i].intersects(boxes[j]) {
+                continue;
+            }
+            let shared: Vec<Pos2> = match (j - i, (i + n - j) % n) {
+                (1, _) => vec![polygon.vertices[j].p],
+                (_, 1) => vec![polygon.vertices[i].p],
+                _ => Vec::new(),
+            };
+            points.extend(
+                segment_intersections(segments[i], segments[j])
+                    .into_iter()
+                    .filter(|p| {
+                        shared
+                            .iter()
+                            .all(|v| v.distance(*p) > constants::INTERSECTION_MERGE_DIST)
+                    }),
+            );
+        }
+    }
+    dedup_points(&mut points);
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polygon_with_curve(c0: Pos2, c1: Pos2) -> Polygon {
+        let (p0, p1) = (Pos2::new(0.0, 0.0), Pos2::new(100.0, 0.0));
+        Polygon::from_segments(
+            &[
+                Segment::Cubic([p0, c0, c1, p1]),
+                Segment::Line([p1, Pos2::new(100.0, 100.0)]),
+                Segment::Line([Pos2::new(100.0, 100.0), p0]),
+            ],
+            false,
+        )
+    }
+
+    #[test]
+    fn self_intersections_finds_the_loop_of_a_curve() {
+        let polygon = polygon_with_curve(Pos2::new(150.0, -100.0), Pos2::new(-50.0, -100.0));
+        let points = self_intersections(&polygon);
+        assert_eq!(points.len(), 1, "{points:?}");
+        // the curve is symmetric, so it crosses itself on its axis
+        assert!((points[0].x - 50.0).abs() < 1e-6, "{points:?}");
+    }
+
+    #[test]
+    fn self_intersections_ignores_curves_without_loops() {
+        let polygon = polygon_with_curve(Pos2::new(30.0, -50.0), Pos2::new(70.0, -50.0));
+        assert!(self_intersections(&polygon).is_empty());
+    }
+}
@@ -0,0 +1,22 @@
+// the polygon model, its constraints and the geometry built on top of it,
+// without anything needed for drawing or editing it on the screen
+
+pub mod boolean;
+pub mod calc;
+pub mod constants;
+pub mod dimension;
+pub mod document;
+pub mod fit;
+pub mod hull;
+pub mod intersection;
+pub mod math;
+pub mod offset;
+pub mod polygon;
+pub mod primitives;
+pub mod properties;
+pub mod segment;
+pub mod simplify;
+pub mod transform;
+pub mod triangulation;
+pub mod units;
+pub mod vertex;
@@ -0,0 +1,391 @@
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+// points and vectors of the model, the y axis points down like on the screen
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Pos2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vec2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+// an axis-aligned box, empty if min lies past max
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub min: Pos2,
+    pub max: Pos2,
+}
+
+pub const fn pos2(x: f64, y: f64) -> Pos2 {
+    Pos2 { x, y }
+}
+
+pub const fn vec2(x: f64, y: f64) -> Vec2 {
+    Vec2 { x, y }
+}
+
+impl Pos2 {
+    pub const ZERO: Self = pos2(0.0, 0.0);
+
+    pub const fn new(x: f64, y: f64) -> Self {
+        pos2(x, y)
+    }
+
+    pub fn to_vec2(self) -> Vec2 {
+        vec2(self.x, self.y)
+    }
+
+    pub fn distance(self, other: Self) -> f64 {
+        (self - other).length()
+    }
+
+    pub fn distance_sq(self, other: Self) -> f64 {
+        (self - other).length_sq()
+    }
+
+    pub fn lerp(self, other: Self, t: f64) -> Self {
+        pos2(
+            (1.0 - t) * self.x + t * other.x,
+            (1.0 - t) * self.y + t * other.y,
+        )
+    }
+
+    pub fn min(self, other: Self) -> Self {
+        pos2(self.x.min(other.x), self.y.min(other.y))
+    }
+
+    pub fn max(self, other: Self) -> Self {
+        pos2(self.x.max(other.x), self.y.max(other.y))
+    }
+
+    pub fn is_finite(self) -> bool {
+        self.x.is_finite() && self.y.is_finite()
+    }
+}
+
+impl Vec2 {
+    pub const ZERO: Self = vec2(0.0, 0.0);
+    pub const X: Self = vec2(1.0, 0.0);
+    pub const Y: Self = vec2(0.0, 1.0);
+
+    pub const fn new(x: f64, y: f64) -> Self {
+        vec2(x, y)
+    }
+
+    pub const fn splat(v: f64) -> Self {
+        vec2(v, v)
+    }
+
+    // the unit vector at the given angle from the x axis
+    pub fn angled(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        vec2(cos, sin)
+    }
+
+    pub fn to_pos2(self) -> Pos2 {
+        pos2(self.x, self.y)
+    }
+
+    pub fn length(self) -> f64 {
+        self.x.hypot(self.y)
+    }
+
+    pub fn length_sq(self) -> f64 {
+        self.x * self.x + self.y * self.y
+    }
+
+    pub fn angle(self) -> f64 {
+        self.y.atan2(self.x)
+    }
+
+    pub fn dot(self, other: Self) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    pub fn normalized(self) -> Self {
+        let len = self.length();
+        if len <= 0.0 { self } else { self / len }
+    }
+
+    // rotated by a quarter turn, counterclockwise on the screen
+    pub fn rot90(self) -> Self {
+        vec2(self.y, -self.x)
+    }
+
+    pub fn abs(self) -> Self {
+        vec2(self.x.abs(), self.y.abs())
+    }
+
+    pub fn is_finite(self) -> bool {
+        self.x.is_finite() && self.y.is_finite()
+    }
+}
+
+impl Rect {
+    // contains nothing, the neutral element of union
+    pub const NOTHING: Self = Self {
+        min: pos2(f64::INFINITY, f64::INFINITY),
+        max: pos2(f64::NEG_INFINITY, f64::NEG_INFINITY),
+    };
+
+    pub fn from_min_max(min: Pos2, max: Pos2) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_center_size(center: Pos2, size: Vec2) -> Self {
+        Self::from_min_max(center - size / 2.0, center + size / 2.0)
+    }
+
+    pub fn from_two_pos(a: Pos2, b: Pos2) -> Self {
+        Self::from_min_max(a.min(b), a.max(b))
+    }
+
+    pub fn from_points(points: &[Pos2]) -> Self {
+        points.iter().fold(Self::NOTHING, |rect, &p| {
+            Self::from_min_max(rect.min.min(p), rect.max.max(p))
+        })
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    pub fn center(&self) -> Pos2 {
+        self.min.lerp(self.max, 0.5)
+    }
+
+    pub fn left_top(&self) -> Pos2 {
+        self.min
+    }
+
+    pub fn right_top(&self) -> Pos2 {
+        pos2(self.max.x, self.min.y)
+    }
+
+    pub fn left_bottom(&self) -> Pos2 {
+        pos2(self.min.x, self.max.y)
+    }
+
+    pub fn right_bottom(&self) -> Pos2 {
+        self.max
+    }
+
+    pub fn center_top(&self) -> Pos2 {
+        pos2(self.center().x, self.min.y)
+    }
+
+    pub fn center_bottom(&self) -> Pos2 {
+        pos2(self.center().x, self.max.y)
+    }
+
+    pub fn left_center(&self) -> Pos2 {
+        pos2(self.min.x, self.center().y)
+    }
+
+    pub fn right_center(&self) -> Pos2 {
+        pos2(self.max.x, self.center().y)
+    }
+
+    pub fn contains(&self, p: Pos2) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x) && (self.min.y..=self.max.y).contains(&p.y)
+    }
+
+    pub fn intersects(&self, other: Self) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self::from_min_max(self.min.min(other.min), self.max.max(other.max))
+    }
+
+    pub fn expand(self, amount: f64) -> Self {
+        let d = Vec2::splat(amount);
+        Self::from_min_max(self.min - d, self.max + d)
+    }
+
+    pub fn shrink(self, amount: f64) -> Self {
+        self.expand(-amount)
+    }
+}
+
+impl From<(f64, f64)> for Pos2 {
+    fn from((x, y): (f64, f64)) -> Self {
+        pos2(x, y)
+    }
+}
+
+impl Sub for Pos2 {
+    type Output = Vec2;
+
+    fn sub(self, other: Self) -> Vec2 {
+        vec2(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Add<Vec2> for Pos2 {
+    type Output = Pos2;
+
+    fn add(self, v: Vec2) -> Pos2 {
+        pos2(self.x + v.x, self.y + v.y)
+    }
+}
+
+impl Sub<Vec2> for Pos2 {
+    type Output = Pos2;
+
+    fn sub(self, v: Vec2) -> Pos2 {
+        pos2(self.x - v.x, self.y - v.y)
+    }
+}
+
+impl AddAssign<Vec2> for Pos2 {
+    fn add_assign(&mut self, v: Vec2) {
+        *self = *self + v;
+    }
+}
+
+impl SubAssign<Vec2> for Pos2 {
+    fn sub_assign(&mut self, v: Vec2) {
+        *self = *self - v;
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, other: Self) -> Vec2 {
+        vec2(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, other: Self) -> Vec2 {
+        vec2(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Vec2 {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Vec2 {
+        vec2(-self.x, -self.y)
+    }
+}
+
+impl Mul<f64> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, s: f64) -> Vec2 {
+        vec2(self.x * s, self.y * s)
+    }
+}
+
+impl Mul<Vec2> for f64 {
+    type Output = Vec2;
+
+    fn mul(self, v: Vec2) -> Vec2 {
+        v * self
+    }
+}
+
+// component-wise
+impl Mul for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, other: Self) -> Vec2 {
+        vec2(self.x * other.x, self.y * other.y)
+    }
+}
+
+impl Div<f64> for Vec2 {
+    type Output = Vec2;
+
+    fn div(self, s: f64) -> Vec2 {
+        vec2(self.x / s, self.y / s)
+    }
+}
+
+impl MulAssign<f64> for Vec2 {
+    fn mul_assign(&mut self, s: f64) {
+        *self = *self * s;
+    }
+}
+
+impl DivAssign<f64> for Vec2 {
+    fn div_assign(&mut self, s: f64) {
+        *self = *self / s;
+    }
+}
+
+// conversions to and from the types used for drawing,
+// which have single precision since they only need to be exact up to a pixel
+#[cfg(feature = "egui")]
+mod egui_conversions {
+    use super::{Pos2, Rect, Vec2, pos2, vec2};
+
+    impl From<Pos2> for emath::Pos2 {
+        fn from(p: Pos2) -> Self {
+            emath::pos2(p.x as f32, p.y as f32)
+        }
+    }
+
+    impl From<emath::Pos2> for Pos2 {
+        fn from(p: emath::Pos2) -> Self {
+            pos2(p.x.into(), p.y.into())
+        }
+    }
+
+    impl From<Vec2> for emath::Vec2 {
+        fn from(v: Vec2) -> Self {
+            emath::vec2(v.x as f32, v.y as f32)
+        }
+    }
+
+    impl From<emath::Vec2> for Vec2 {
+        fn from(v: emath::Vec2) -> Self {
+            vec2(v.x.into(), v.y.into())
+        }
+    }
+
+    impl From<Rect> for emath::Rect {
+        fn from(r: Rect) -> Self {
+            emath::Rect::from_min_max(r.min.into(), r.max.into())
+        }
+    }
+
+    impl From<emath::Rect> for Rect {
+        fn from(r: emath::Rect) -> Self {
+            Rect::from_min_max(r.min.into(), r.max.into())
+        }
+    }
+}
@@ -0,0 +1,403 @@
+use std::f64::consts;
+
+use crate::{
+    calc::{self, ArcData},
+    constants, intersection,
+    math::{Pos2, Vec2},
+    polygon::Polygon,
+    segment::Segment,
+};
+
+// how the offset edges are connected around the outer side of a corner
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum JoinStyle {
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+// the segment moved by d along its normal tangent.rot90()
+fn offset_segment(segment: Segment, d: f64, tolerance: f64, pieces: &mut Vec<Segment>) {
+    match segment {
+        Segment::Line([p0, p1]) => {
+            let n = d * segment.tangent(0.0).rot90();
+            pieces.push(Segment::Line([p0 + n, p1 + n]));
+        }
+        Segment::Cubic(p) => offset_cubic(p, d, tolerance, 0, pieces),
+        // offsets of arcs are concentric arcs, the normal points
+        // away from the center for clockwise arcs and towards it otherwise
+        Segment::Arc(arc) => {
+            let r = arc.radius + d * arc.sweep.signum();
+            if r >= 0.0 {
+                pieces.push(Segment::Arc(ArcData { radius: r, ..arc }));
+                return;
+            }
+            // an inset deeper than the radius collapses the arc, its offset ends
+            // are joined through the center, so the pieces go against the outline
+            // and the loop they make gets trimmed like the inner sides of corners
+            let offset = ArcData { radius: r, ..arc };
+            pieces.push(Segment::Line([
+                offset.point_at(arc.start_angle),
+                arc.center,
+            ]));
+            pieces.push(Segment::Line([
+                arc.center,
+                offset.point_at(arc.start_angle + arc.sweep),
+            ]));
+        }
+    }
+}
+
+// the offset of a Bézier curve isn't a Bézier curve, so it's approximated by one
+// with the same end tangents and handle lengths chosen to match the offset midpoint,
+// the curve is split in half until the approximation is within tolerance
+fn offset_cubic(p: [Pos2; 4], d: f64, tolerance: f64, depth: u8, pieces: &mut Vec<Segment>) {
+    let segment = Segment::Cubic(p);
+    let offset_point = |t: f64| segment.point_at(t) + d * segment.tangent(t).rot90();
+    let (q0, q1) = (offset_point(0.0), offset_point(1.0));
+    let (t0, t1) = (segment.tangent(0.0), segment.tangent(1.0));
+
+    // the midpoint of the approximation is (q0 + q1) / 2 + 3/8 * (a * t0 - b * t1),
+    // where a and b are the lengths of its handles
+    let rhs = (offset_point(0.5) - calc::midpoint(q0, q1)) * 8.0 / 3.0;
+    let det = calc::cross(t0, -t1);
+    let (a, b) = if det.abs() > constants::DOT_EPS {
+        (calc::cross(rhs, -t1) / det, calc::cross(t0, rhs) / det)
+    } else {
+        (p[0].distance(p[1]), p[2].distance(p[3]))
+    };
+    let approx = Segment::Cubic([q0, q0 + a * t0, q1 - b * t1, q1]);
+
+    let error = [0.25, 0.5, 0.75]
+        .into_iter()
+        .map(|t| approx.point_at(t).distance(offset_point(t)))
+        .fold(0.0, f64::max);
+    // near cusps of the offset the error doesn't go down, so short enough pieces are accepted anyway
+    if depth >= constants::MAX_OFFSET_DEPTH
+        || segment.length() <= tolerance
+        || (a >= 0.0 && b >= 0.0 && error <= tolerance)
+    {
+        pieces.push(approx);
+        return;
+    }
+    let (l, r) = calc::split_cubic_bezier(p[0], p[1], p[2], p[3], 0.5);
+    offset_cubic(l, d, tolerance, depth + 1, pieces);
+    offset_cubic(r, d, tolerance, depth + 1, pieces);
+}
+
+// connects the offsets of two edges meeting at v
+fn join(v: Pos2, t_in: Vec2, t_out: Vec2, d: f64, style: JoinStyle) -> Vec<Segment> {
+    let (e, s) = (v + d * t_in.rot90(), v + d * t_out.rot90());
+    if e.distance(s) < constants::DIST_EPS {
+        return Vec::new();
+    }
+    // on the inner side of the corner the offsets cross each other
+    // and the loop made by this line gets trimmed later
+    let outer = calc::cross(t_in, t_out) * d > 0.0;
+    if !outer {
+        return vec![Segment::Line([e, s])];
+    }
+
+    match style {
+        JoinStyle::Miter => {
+            let m = e + t_in * calc::cross(s - e, t_out) / calc::cross(t_in, t_out);
+            if m.distance(v) <= constants::MITER_LIMIT * d.abs() {
+                vec![Segment::Line([e, m]), Segment::Line([m, s])]
+            } else {
+                vec![Segment::Line([e, s])]
+            }
+        }
+        JoinStyle::Round => {
+            let (u, w) = (e - v, s - v);
+            vec![Segment::Arc(ArcData {
+                center: v,
+                radius: d.abs(),
+                start_angle: u.angle(),
+                sweep: calc::cross(u, w).atan2(u.dot(w)),
+            })]
+        }
+        JoinStyle::Bevel => vec![Segment::Line([e, s])],
+    }
+}
+
+// number of times the closed polyline winds around p,
+// positive for polylines with positive signed area
+fn winding_number(points: &[Pos2], p: Pos2) -> i32 {
+    let angle = (0..points.len())
+        .map(|i| {
+            let (a, b) = (points[i] - p, points[(i + 1) % points.len()] - p);
+            calc::cross(a, b).atan2(a.dot(b))
+        })
+        .sum::<f64>();
+
+    (angle / (2.0 * consts::PI)).round() as i32
+}
+
+// splits the segment at the given parameters
+fn split_at(segment: Segment, mut ts: Vec<f64>) -> Vec<Segment> {
+    ts.sort_by(f64::total_cmp);
+    let mut pieces = Vec::new();
+    let (mut rest, mut prev_t) = (segment, 0.0);
+    for t in ts {
+        if t - prev_t < constants::DOT_EPS || t > 1.0 - constants::DOT_EPS {
+            continue;
+        }
+        let (l, r) = rest.split((t - prev_t) / (1.0 - prev_t));
+        pieces.push(l);
+        (rest, prev_t) = (r, t);
+    }
+    pieces.push(rest);
+
+    pieces
+}
+
+// removes the loops of a closed chain of segments which wind around
+// the wrong way or more than once, by splitting the segments where they cross
+// and keeping only the pieces that separate the points with positive
+// winding number (relative to the orientation of the original outline) from the rest
+fn remove_loops(segments: Vec<Segment>, orientation: i32) -> Vec<Segment> {
+    let n = segments.len();
+    let boxes: Vec<_> = segments.iter().map(|s| s.bounding_box()).collect();
+    let mut cuts = vec![Vec::new(); n];
+    for i in 0..n {
+        for j in (i + 1)..n {
+            if !boxes[i].expand(constants::EPS).intersects(boxes[j]) {
+                continue;
+            }
+            // neighbors always meet at their shared endpoint
+            let shared: Vec<Pos2> = match (j - i, (i + n - j) % n) {
+                (1, _) => vec![segments[j].start()],
+                (_, 1) => vec![segments[i].start()],
+                _ => Vec::new(),
+            };
+            for p in intersection::segment_intersections(segments[i], segments[j]) {
+                if shared
+                    .iter()
+                    .all(|q| q.distance(p) > constants::INTERSECTION_MERGE_DIST)
+                {
+                    cuts[i].push(segments[i].nearest_parameter(p));
+                    cuts[j].push(segments[j].nearest_parameter(p));
+                }
+            }
+            // overlapping segments have no single intersection point, but cutting
+            // each of them at the ends of the other gives them common pieces
+            for (a, b) in [(i, j), (j, i)] {
+                for q in [segments[b].start(), segments[b].end()] {
+                    let t = segments[a].nearest_parameter(q);
+                    if segments[a].point_at(t).distance(q) < constants::DIST_EPS {
+                        cuts[a].push(t);
+                    }
+                }
+            }
+        }
+    }
+
+    let outline: Vec<Pos2> = segments
+        .iter()
+        .flat_map(|segment| {
+            let mut points = segment.points(constants::INTERSECTION_TOLERANCE);
+            points.pop();
+            points
+        })
+        .collect();
+    let mut pieces: Vec<Segment> = Vec::new();
+    for piece in segments
+        .into_iter()
+        .zip(cuts)
+        .flat_map(|(segment, ts)| split_at(segment, ts))
+        .filter(|piece| {
+            let (m, n) = (piece.point_at(0.5), piece.tangent(0.5).rot90());
+            let probe = constants::OFFSET_PROBE_DIST;
+            let n = orientation as f64 * n;
+            let inner = orientation * winding_number(&outline, m - probe * n);
+            let outer = orientation * winding_number(&outline, m + probe * n);
+            inner > 0 && outer <= 0
+        })
+    {
+        // overlapping pieces going the same way (e.g. miters of two edges
+        // meeting head-on) are the same piece of the boundary
+        let same = |other: &Segment| {
+            [0.0, 0.5, 1.0].into_iter().all(|t| {
+                other.point_at(t).distance(piece.point_at(t)) < constants::INTERSECTION_MERGE_DIST
+            })
+        };
+        if !pieces.iter().any(same) {
+            pieces.push(piece);
+        }
+    }
+
+    pieces
+}
+
+// joins the pieces into closed chains by following matching endpoints
+fn chain(mut pieces: Vec<Segment>) -> Vec<Vec<Segment>> {
+    let mut chains = Vec::new();
+    while let Some(first) = pieces.pop() {
+        let mut chain = vec![first];
+        loop {
+            let end = chain[chain.len() - 1].end();
+            if end.distance(first.start()) < constants::INTERSECTION_MERGE_DIST && chain.len() > 1 {
+                break;
+            }
+            let next = pieces
+                .iter()
+                .enumerate()
+                .map(|(i, piece)| (i, piece.start().distance(end)))
+                .filter(|&(_, dist)| dist < constants::INTERSECTION_MERGE_DIST)
+                .min_by(|(_, d0), (_, d1)| d0.total_cmp(d1));
+            // if the chain can't be continued, its ends get connected by a straight line
+            let Some((i, _)) = next else {
+                break;
+            };
+            chain.push(pieces.swap_remove(i));
+        }
+        chains.push(chain);
+    }
+
+    chains
+}
+
+// merges neighboring straight pieces lying on the same line
+fn merge_lines(chain: Vec<Segment>) -> Vec<Segment> {
+    let mut merged: Vec<Segment> = Vec::with_capacity(chain.len());
+    for segment in chain {
+        if let (Some(Segment::Line([a, b])), Segment::Line([_, c])) = (merged.last_mut(), segment)
+            && calc::distance_to_segment(*b, *a, c) < constants::DIST_EPS
+        {
+            *b = c;
+        } else {
+            merged.push(segment);
+        }
+    }
+    let n = merged.len();
+    if let (Some(Segment::Line([a, b])), Some(Segment::Line([c, _]))) =
+        (merged.last().copied(), merged.first_mut())
+        && n > 3
+        && calc::distance_to_segment(a, *c, b) < constants::DIST_EPS
+    {
+        *c = a;
+        merged.pop();
+    }
+
+    merged
+}
+
+// polygons whose outlines are at the given distance from the polygon's outline,
+// positive distances go outwards and negative ones go inwards;
+// a single polygon can fall apart into several ones and gain or lose holes
+pub fn offset(polygon: &Polygon, distance: f64, style: JoinStyle, tolerance: f64) -> Vec<Polygon> {
+    let segments: Vec<_> = polygon
+        .segments()
+        .filter(|segment| segment.length() >= constants::EPS)
+        .collect();
+    let area = segments.iter().map(Segment::area).sum::<f64>();
+    if segments.is_empty() || area.abs() < constants::EPS || distance.abs() < constants::EPS {
+        return Vec::new();
+    }
+    // for positive signed area tangent.rot90() points outwards
+    let d = distance * area.signum();
+
+    let n = segments.len();
+    let mut raw = Vec::new();
+    for i in 0..n {
+        let next = &segments[(i + 1) % n];
+        offset_segment(segments[i], d, tolerance, &mut raw);
+        raw.extend(join(
+            next.start(),
+            segments[i].tangent(1.0),
+            next.tangent(0.0),
+            d,
+            style,
+        ));
+    }
+    raw.retain(|segment| segment.length() >= constants::EPS);
+
+    chain(remove_loops(raw, area.signum() as i32))
+        .into_iter()
+        .map(merge_lines)
+        .filter_map(|mut chain| {
+            // a polygon needs at least three vertices
+            while chain.len() < 3 {
+                let (i, _) = chain
+                    .iter()
+                    .enumerate()
+                    .max_by(|(_, a), (_, b)| a.length().total_cmp(&b.length()))?;
+                let (l, r) = chain[i].split(0.5);
+                chain[i] = l;
+                chain.insert(i + 1, r);
+            }
+            let chain_area = chain.iter().map(Segment::area).sum::<f64>();
+            // chains going around the other way are holes
+            (chain_area.abs() >= constants::EPS)
+                .then(|| Polygon::from_segments(&chain, polygon.hole != (chain_area * area < 0.0)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{CurveKind, Primitive};
+
+    fn area(polygon: &Polygon) -> f64 {
+        polygon
+            .segments()
+            .map(|segment| segment.area())
+            .sum::<f64>()
+            .abs()
+    }
+
+    fn offset_areas(polygon: &Polygon, distance: f64) -> Vec<f64> {
+        offset(polygon, distance, JoinStyle::Miter, 0.01)
+            .iter()
+            .map(area)
+            .collect()
+    }
+
+    #[test]
+    fn offset_moves_the_sides_of_a_square() {
+        let square = Primitive::Rectangle
+            .build(Pos2::new(0.0, 0.0), Pos2::new(100.0, 100.0))
+            .unwrap();
+        let areas = offset_areas(&square, 10.0);
+        assert!(
+            areas.len() == 1 && (areas[0] - 120.0 * 120.0).abs() < 1e-6,
+            "{areas:?}"
+        );
+        let areas = offset_areas(&square, -10.0);
+        assert!(
+            areas.len() == 1 && (areas[0] - 80.0 * 80.0).abs() < 1e-6,
+            "{areas:?}"
+        );
+        assert!(offset_areas(&square, -60.0).is_empty());
+    }
+
+    #[test]
+    fn offset_moves_the_outline_of_a_circle() {
+        let circle = Primitive::Circle(CurveKind::Arc)
+            .build(Pos2::new(0.0, 0.0), Pos2::new(50.0, 0.0))
+            .unwrap();
+        let areas = offset_areas(&circle, -20.0);
+        assert!(
+            areas.len() == 1 && (areas[0] - consts::PI * 30.0 * 30.0).abs() < 1e-6,
+            "{areas:?}"
+        );
+        for distance in [-50.0, -60.0, -200.0] {
+            let areas = offset_areas(&circle, distance);
+            assert!(areas.is_empty(), "{distance}: {areas:?}");
+        }
+    }
+
+    #[test]
+    fn inset_turns_rounded_corners_sharp() {
+        let rounded = Primitive::RoundedRectangle(10.0)
+            .build(Pos2::new(0.0, 0.0), Pos2::new(100.0, 100.0))
+            .unwrap();
+        let areas = offset_areas(&rounded, -20.0);
+        assert!(
+            areas.len() == 1 && (areas[0] - 60.0 * 60.0).abs() < 1e-6,
+            "{areas:?}"
+        );
+    }
+}
@@ -0,0 +1,1454 @@
+use crate::{
+    boolean::{self, Contour},
+    calc, constants, fit, intersection,
+    math::{Pos2, Vec2},
+    segment::Segment,
+    transform::Transform,
+    vertex::{CircleArc, CubicBezier, EdgeConstraint, Vertex, VertexConstraint},
+};
+
+#[derive(Debug)]
+pub struct Polygon {
+    pub vertices: Vec<Vertex>,
+    // holes cut out the interior of the shapes they lie in
+    pub hole: bool,
+    // refuse to move vertices if it makes the edges cross each other
+    pub reject_self_intersections: bool,
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Vertex>) -> Self {
+        Self {
+            vertices,
+            hole: false,
+            reject_self_intersections: false,
+        }
+    }
+
+    // builds a polygon with straight edges from a closed polyline
+    pub fn from_points(points: &[Pos2], hole: bool) -> Self {
+        Self {
+            hole,
+            ..Self::new(points.iter().map(|&p| Vertex::new(p)).collect())
+        }
+    }
+
+    // builds a polygon from a closed chain of segments, the end of each segment
+    // is taken to be the start of the next one; all joints are left at G0
+    // so that the constraints don't move anything
+    pub fn from_segments(segments: &[Segment], hole: bool) -> Self {
+        let vertices = segments
+            .iter()
+            .map(|segment| {
+                let mut v = Vertex::new(segment.start());
+                v.vertex_c = VertexConstraint::G0;
+                match segment {
+                    Segment::Line(_) => (),
+                    Segment::Cubic([_, c0, c1, _]) => {
+                        v.bezier = Some(CubicBezier::new([*c0, *c1]));
+                    }
+                    Segment::Arc(arc) => {
+                        v.arc = Some(CircleArc::new(
+                            Some(arc.radius),
+                            arc.sweep.abs() > std::f64::consts::PI,
+                            arc.sweep > 0.0,
+                        ));
+                    }
+                }
+                v
+            })
+            .collect();
+
+        Self {
+            hole,
+            ..Self::new(vertices)
+        }
+    }
+
+    // the whole outline as a closed polyline (the last point isn't repeated)
+    pub fn outline_points(&self, tolerance: f64) -> Vec<Pos2> {
+        self.segments()
+            .flat_map(|segment| {
+                let mut points = segment.points(tolerance);
+                points.pop();
+                points
+            })
+            .collect()
+    }
+
+    // the outline together with the outlines of the holes among the shapes lying inside it
+    pub fn region<'a>(
+        &self,
+        shapes: impl Iterator<Item = &'a Polygon>,
+        tolerance: f64,
+    ) -> Vec<Contour> {
+        let outline = self.outline_points(tolerance);
+        let holes = shapes
+            .filter(|hole| {
+                !self.hole
+                    && hole.hole
+                    && !std::ptr::eq(*hole, self)
+                    && boolean::contains(std::slice::from_ref(&outline), hole.vertices[0].p)
+            })
+            .map(|hole| hole.outline_points(tolerance))
+            .collect::<Vec<_>>();
+
+        std::iter::once(outline).chain(holes).collect()
+    }
+
+    pub fn is_near(&self, p: Pos2, tolerance: f64) -> bool {
+        self.vertices.iter().any(|v| v.is_near(p))
+            || (0..self.vertices.len()).any(|e_i| self.is_near_edge(e_i, p, tolerance))
+    }
+
+    pub fn is_self_intersecting(&self) -> bool {
+        !intersection::self_intersections(self).is_empty()
+    }
+
+    // the move is valid if all constraints hold and, if required, no edges cross
+    fn is_valid_after_move(&mut self, v_i: usize) -> bool {
+        self.resolve_constraints(v_i)
+            && !(self.reject_self_intersections && self.is_self_intersecting())
+    }
+
+    fn next_i(&self, i: usize) -> usize {
+        (i + 1) % self.vertices.len()
+    }
+
+    fn prev_i(&self, i: usize) -> usize {
+        (i + self.vertices.len() - 1) % self.vertices.len()
+    }
+
+    pub fn edge_len(&self, e_i: usize) -> f64 {
+        self.vertices[e_i]
+            .p
+            .distance(self.vertices[self.next_i(e_i)].p)
+    }
+
+    // geometry of the edge e_i
+    pub fn segment(&self, e_i: usize) -> Segment {
+        let next_i = self.next_i(e_i);
+        let (p0, p1) = (self.vertices[e_i].p, self.vertices[next_i].p);
+        if let Some(bezier) = self.vertices[e_i].bezier {
+            Segment::Cubic([p0, bezier.control[0], bezier.control[1], p1])
+        } else if self.vertices[e_i].arc.is_some() {
+            Segment::Arc(self.arc_data(e_i))
+        } else {
+            Segment::Line([p0, p1])
+        }
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = Segment> + '_ {
+        (0..self.vertices.len()).map(|e_i| self.segment(e_i))
+    }
+
+    // points of a polyline approximating the edge e_i
+    // up to the given tolerance (exact for straight edges)
+    pub fn edge_points(&self, e_i: usize, tolerance: f64) -> Vec<Pos2> {
+        self.segment(e_i).points(tolerance)
+    }
+
+    pub fn arc_data(&self, e_i: usize) -> calc::ArcData {
+        let next_i = self.next_i(e_i);
+        calc::circular_arc_data(
+            self.vertices[e_i],
+            self.vertices[next_i],
+            self.vertices[self.prev_i(e_i)],
+            self.vertices[self.next_i(next_i)],
+        )
+    }
+
+    pub fn is_near_edge(&self, e_i: usize, p: Pos2, tolerance: f64) -> bool {
+        calc::distance_to_polyline(p, &self.edge_points(e_i, tolerance))
+            <= constants::SIZE_HITRADIUS
+    }
+
+    pub fn has_vertical_neighbor(&self, e_i: usize) -> bool {
+        let prev_i = (e_i + self.vertices.len() - 1) % self.vertices.len();
+        let next_i = (e_i + 1) % self.vertices.len();
+
+        matches!(self.vertices[prev_i].edge_c, Some(EdgeConstraint::Vertical))
+            || matches!(self.vertices[next_i].edge_c, Some(EdgeConstraint::Vertical))
+    }
+
+    pub fn has_horizontal_neighbor(&self, e_i: usize) -> bool {
+        let prev_i = (e_i + self.vertices.len() - 1) % self.vertices.len();
+        let next_i = (e_i + 1) % self.vertices.len();
+
+        matches!(
+            self.vertices[prev_i].edge_c,
+            Some(EdgeConstraint::Horizontal)
+        ) || matches!(
+            self.vertices[next_i].edge_c,
+            Some(EdgeConstraint::Horizontal)
+        )
+    }
+
+    pub fn is_bezier_start(&self, v_i: usize) -> bool {
+        v_i < self.vertices.len() && self.vertices[v_i].bezier.is_some()
+    }
+
+    pub fn is_arc_start(&self, v_i: usize) -> bool {
+        v_i < self.vertices.len() && self.vertices[v_i].arc.is_some()
+    }
+
+    pub fn is_arc_end(&self, v_i: usize) -> bool {
+        self.vertices[self.prev_i(v_i)].arc.is_some()
+    }
+
+    pub fn has_arc_radius(&self, e_i: usize) -> bool {
+        self.vertices[e_i]
+            .arc
+            .is_some_and(|arc| arc.radius.is_some())
+    }
+
+    // the radius of an arc can be chosen only if neither end is tangent to its neighbor
+    pub fn can_set_arc_radius(&self, e_i: usize) -> bool {
+        self.is_arc_start(e_i)
+            && matches!(self.vertices[e_i].vertex_c, VertexConstraint::G0)
+            && matches!(
+                self.vertices[self.next_i(e_i)].vertex_c,
+                VertexConstraint::G0
+            )
+    }
+
+    pub fn apply_constraint(&mut self, v_i: usize) {
+        let next_i = self.next_i(v_i);
+        let prev_i = self.prev_i(v_i);
+        let (p0, p1, p2, p3) = (
+            self.vertices[prev_i].p,
+            self.vertices[v_i].p,
+            self.vertices[next_i].p,
+            self.vertices[self.next_i(next_i)].p,
+        );
+        let p1_c = self.vertices[v_i].vertex_c;
+        let p2_c = self.vertices[next_i].vertex_c;
+        let p3_c = self.vertices[self.next_i(next_i)].vertex_c;
+        let prev_bezier = self.vertices[prev_i].bezier;
+        let next_bezier = self.vertices[next_i].bezier;
+        match &mut self.vertices[v_i].bezier {
+            Some(bezier) => {
+                if let Some(prev_bezier) = prev_bezier {
+                    bezier.control[0] = enforce_joint_continuity(
+                        bezier.control[0],
+                        prev_bezier.control[1],
+                        p1,
+                        p1_c,
+                    );
+                } else {
+                    bezier.control[0] =
+                        enforce_continuity_constraint(p0, p1, bezier.control[0], p1_c);
+                }
+                if let Some(next_bezier) = next_bezier {
+                    bezier.control[1] = enforce_joint_continuity(
+                        bezier.control[1],
+                        next_bezier.control[0],
+                        p2,
+                        p2_c,
+                    );
+                } else {
+                    bezier.control[1] =
+                        enforce_continuity_constraint(p3, p2, bezier.control[1], p2_c);
+                }
+                // curvature continuity at a joint is enforced by moving the second
+                // control point of the curve whose other end is free (G0),
+                // preferring the curve that comes after the joint
+                if let Some(prev_bezier) = prev_bezier
+                    && is_curvature_continuous(p1_c)
+                    && matches!(p2_c, VertexConstraint::G0)
+                {
+                    bezier.control[1] =
+                        enforce_curvature_continuity(prev_bezier.control, p1, bezier.control, p1_c);
+                }
+                if let Some(next_bezier) = next_bezier
+                    && is_curvature_continuous(p2_c)
+                    && matches!(p1_c, VertexConstraint::G0)
+                    && !matches!(p3_c, VertexConstraint::G0)
+                {
+                    // same as above, but going backwards
+                    let reversed = enforce_curvature_continuity(
+                        [next_bezier.control[1], next_bezier.control[0]],
+                        p2,
+                        [bezier.control[1], bezier.control[0]],
+                        p2_c,
+                    );
+                    bezier.control[0] = reversed;
+                }
+            }
+            None => {
+                if let Some(c) = self.vertices[v_i].edge_c {
+                    let fixed_p = self.vertices[v_i].p;
+                    let free_p = self.vertices[next_i].p;
+                    match c {
+                        EdgeConstraint::Vertical => self.vertices[next_i].p.x = fixed_p.x,
+                        EdgeConstraint::Horizontal => self.vertices[next_i].p.y = fixed_p.y,
+                        EdgeConstraint::DiagonalUp => {
+                            self.vertices[next_i].p =
+                                calc::project_onto_diagonal_up(fixed_p, free_p);
+                        }
+                        EdgeConstraint::DiagonalDown => {
+                            self.vertices[next_i].p =
+                                calc::project_onto_diagonal_down(fixed_p, free_p);
+                        }
+                        EdgeConstraint::FixedLength(len) => {
+                            self.vertices[next_i].p = calc::rescale(fixed_p, free_p, len);
+                        }
+                    }
+                } else if let Some(r) = self.vertices[v_i].arc.and_then(|arc| arc.radius) {
+                    // the chord of an arc can't be longer than its diameter
+                    self.vertices[next_i].p =
+                        calc::rescale(self.vertices[v_i].p, self.vertices[next_i].p, 2.0 * r);
+                }
+            }
+        }
+    }
+
+    // checks if the constraint is ok for edge v_i-next(v_i)
+    pub fn check_constraint(&self, v_i: usize) -> bool {
+        let prev_i = self.prev_i(v_i);
+        let next_i = self.next_i(v_i);
+        match self.vertices[v_i].bezier {
+            Some(bezier) => {
+                let (p0, p1, p2, p3) = (
+                    self.vertices[prev_i].p,
+                    self.vertices[v_i].p,
+                    self.vertices[next_i].p,
+                    self.vertices[self.next_i(next_i)].p,
+                );
+                // p0 -> p1 -> Bézier -> p2 -> p3
+                // so we check if G1/C1 holds
+                // when going p0 -> p1 -> Bézier and p3 -> p2 -> Bézier
+                let p1_c = self.vertices[v_i].vertex_c;
+                let p2_c = self.vertices[next_i].vertex_c;
+                let mut res = true;
+                if let Some(prev_bezier) = self.vertices[prev_i].bezier {
+                    res &=
+                        check_joint_continuity(prev_bezier.control[1], bezier.control[0], p1, p1_c);
+                } else {
+                    res &= check_continuity_constraint(p0, p1, bezier.control[0], p1_c);
+                }
+                if let Some(next_bezier) = self.vertices[next_i].bezier {
+                    res &=
+                        check_joint_continuity(bezier.control[1], next_bezier.control[0], p2, p2_c);
+                } else {
+                    res &= check_continuity_constraint(p3, p2, bezier.control[1], p2_c);
+                }
+                // curvature is checked at both joints, otherwise this edge wouldn't be
+                // adjusted when the curve after p2 can't be
+                if let Some(prev_bezier) = self.vertices[prev_i].bezier
+                    && is_curvature_continuous(p1_c)
+                {
+                    res &=
+                        check_curvature_continuity(prev_bezier.control, p1, bezier.control, p1_c);
+                }
+                if let Some(next_bezier) = self.vertices[next_i].bezier
+                    && is_curvature_continuous(p2_c)
+                {
+                    res &=
+                        check_curvature_continuity(bezier.control, p2, next_bezier.control, p2_c);
+                }
+
+                res
+            }
+            None => {
+                if let Some(c) = self.vertices[v_i].edge_c {
+                    let (p0, p1) = (self.vertices[v_i].p, self.vertices[next_i].p);
+                    match c {
+                        EdgeConstraint::Vertical => {
+                            (p0.x - p1.x).abs() < constants::CONSTRAINT_DIST_TOLERANCE
+                        }
+                        EdgeConstraint::Horizontal => {
+                            (p0.y - p1.y).abs() < constants::CONSTRAINT_DIST_TOLERANCE
+                        }
+                        // how far p1 is from the diagonal through p0, along one axis
+                        EdgeConstraint::DiagonalUp => {
+                            ((p1.y - p0.y) + (p1.x - p0.x)).abs()
+                                < constants::CONSTRAINT_DIST_TOLERANCE
+                        }
+                        EdgeConstraint::DiagonalDown => {
+                            ((p1.y - p0.y) - (p1.x - p0.x)).abs()
+                                < constants::CONSTRAINT_DIST_TOLERANCE
+                        }
+                        EdgeConstraint::FixedLength(len) => {
+                            (p0.distance(p1) - len).abs() < constants::CONSTRAINT_DIST_TOLERANCE
+                        }
+                    }
+                } else if let Some(r) = self.vertices[v_i].arc.and_then(|arc| arc.radius) {
+                    let (p0, p1) = (self.vertices[v_i].p, self.vertices[next_i].p);
+                    p0.distance(p1) <= 2.0 * r + constants::CONSTRAINT_DIST_TOLERANCE
+                } else {
+                    true
+                }
+            }
+        }
+    }
+
+    pub fn resolve_constraints(&mut self, start_i: usize) -> bool {
+        for _ in 0..constants::MAX_RESOLVING_ITERS {
+            let mut cur_i = start_i;
+            loop {
+                if !self.check_constraint(cur_i) {
+                    self.apply_constraint(cur_i);
+                }
+                cur_i = self.next_i(cur_i);
+                if cur_i == start_i {
+                    break;
+                }
+            }
+            cur_i = start_i;
+            loop {
+                let prev_i = self.prev_i(cur_i);
+                if !self.check_constraint(prev_i) {
+                    self.apply_constraint(prev_i);
+                }
+                if prev_i == start_i {
+                    break;
+                }
+                cur_i = prev_i;
+            }
+
+            if (0..self.vertices.len()).all(|v_i| self.check_constraint(v_i)) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    // try to move vertex v_i to new_p
+    // returns false and rolls back the move
+    // if it violated some constraint
+    pub fn try_move_vertex(&mut self, v_i: usize, new_p: Pos2) -> bool {
+        let backup = self.vertices.clone();
+        self.vertices[v_i].p = new_p;
+
+        if self.is_valid_after_move(v_i) {
+            true
+        } else {
+            self.vertices = backup;
+            false
+        }
+    }
+
+    // try to move edge e_i (with its control points) by delta,
+    // both ends are moved before the constraints are resolved
+    // so that the edge keeps its shape
+    // returns false and rolls back the move
+    // if it violated some constraint
+    pub fn try_move_edge(&mut self, e_i: usize, delta: Vec2) -> bool {
+        let backup = self.vertices.clone();
+        let next_i = self.next_i(e_i);
+        self.vertices[e_i].p += delta;
+        self.vertices[next_i].p += delta;
+        if let Some(bezier) = &mut self.vertices[e_i].bezier {
+            bezier.control = bezier.control.map(|c| c + delta);
+        }
+
+        if self.is_valid_after_move(e_i) {
+            true
+        } else {
+            self.vertices = backup;
+            false
+        }
+    }
+
+    // set the given constraint to edge e_i
+    // rollback the change if some constraint was violated
+    pub fn try_set_edge_constraint(&mut self, e_i: usize, edge_c: EdgeConstraint) {
+        let backup = self.vertices.clone();
+        self.vertices[e_i].edge_c = Some(edge_c);
+        if !self.resolve_constraints(e_i) {
+            self.vertices = backup;
+        }
+    }
+
+    pub fn try_set_vertex_constraint(&mut self, v_i: usize, vertex_c: VertexConstraint) {
+        let backup = self.vertices.clone();
+        self.vertices[v_i].vertex_c = vertex_c;
+        if is_curvature_continuous(vertex_c) {
+            self.free_curvature_end(v_i);
+        }
+        if !self.resolve_constraints(v_i) {
+            self.vertices = backup;
+        }
+    }
+
+    // curvature continuity at the joint v_i can be enforced only if one of the joined
+    // curves has its other end free (G0), so we relax one of them if needed;
+    // the caller can tell by comparing the constraints of the neighbors
+    fn free_curvature_end(&mut self, v_i: usize) {
+        let (prev_i, next_i) = (self.prev_i(v_i), self.next_i(v_i));
+        let is_free = |c| matches!(c, VertexConstraint::G0);
+        if is_free(self.vertices[prev_i].vertex_c) || is_free(self.vertices[next_i].vertex_c) {
+            return;
+        }
+        if let Some(end_i) = [next_i, prev_i]
+            .into_iter()
+            .find(|&i| !is_curvature_continuous(self.vertices[i].vertex_c))
+        {
+            self.vertices[end_i].vertex_c = VertexConstraint::G0;
+        }
+    }
+
+    pub fn move_polygon(&mut self, delta: Vec2) {
+        for v in self.vertices.iter_mut() {
+            v.p += delta;
+            if let Some(bezier) = &mut v.bezier {
+                bezier.control[0] += delta;
+                bezier.control[1] += delta;
+            }
+        }
+    }
+
+    // applies the transform to the vertices and control points,
+    // directional constraints follow the edges if they stay vertical or diagonal,
+    // otherwise they're dropped if `release` is set or the transform is refused,
+    // fixed lengths change together with the edges;
+    // arcs would stop being circular under a non-uniform scale, so it's refused
+    // rollback the change if some constraint was violated
+    pub fn try_transform(&mut self, transform: Transform, release: bool) -> bool {
+        if !transform.is_uniform() && self.vertices.iter().any(|v| v.arc.is_some()) {
+            return false;
+        }
+        let backup = self.vertices.clone();
+        let det = transform.det();
+        for v in self.vertices.iter_mut() {
+            v.p = transform.apply(v.p);
+            if let Some(bezier) = &mut v.bezier {
+                bezier.control = bezier.control.map(|c| transform.apply(c));
+            }
+            if let Some(arc) = &mut v.arc {
+                arc.radius = arc.radius.map(|r| r * det.abs().sqrt());
+                arc.clockwise ^= det < 0.0;
+            }
+        }
+        let mut valid = true;
+        for e_i in 0..self.vertices.len() {
+            let d = self.vertices[self.next_i(e_i)].p - self.vertices[e_i].p;
+            self.vertices[e_i].edge_c = match self.vertices[e_i].edge_c {
+                Some(EdgeConstraint::FixedLength(_)) => {
+                    let len = d.length();
+                    valid &= len >= constants::SIZE_MIN_EDGE_LENGTH;
+                    Some(EdgeConstraint::FixedLength(len))
+                }
+                Some(_) => {
+                    let c = direction_constraint(d);
+                    valid &= c.is_some() || release;
+                    c
+                }
+                None => None,
+            };
+        }
+        if !valid || !self.resolve_constraints(0) {
+            self.vertices = backup;
+            return false;
+        }
+
+        true
+    }
+
+    pub fn remove_vertex(&mut self, mut v_i: usize) {
+        if self.vertices.len() == 3 {
+            return;
+        }
+        let prev_i = (v_i + self.vertices.len() - 1) % self.vertices.len();
+        self.vertices[prev_i].edge_c = None;
+        self.vertices[prev_i].bezier = None;
+        self.vertices[prev_i].arc = None;
+        self.vertices.remove(v_i);
+        if v_i == self.vertices.len() {
+            v_i = 0;
+        }
+        // this can't fail since removing a vertex
+        // doesn't tighten any constraints
+        let _ = self.try_move_vertex(v_i, self.vertices[v_i].p);
+    }
+
+    pub fn subdivide_edge(&mut self, e_i: usize) {
+        self.vertices[e_i].edge_c = None;
+        self.vertices[e_i].bezier = None;
+        let next_i = (e_i + 1) % self.vertices.len();
+        self.vertices.insert(
+            next_i,
+            Vertex::new(calc::midpoint(
+                self.vertices[e_i].p,
+                self.vertices[next_i].p,
+            )),
+        );
+    }
+
+    // splits the Bézier segment e_i at parameter t into two segments
+    // meeting at a new vertex, without changing the shape of the curve
+    pub fn split_bezier(&mut self, e_i: usize, t: f64) {
+        let Some(bezier) = self.vertices[e_i].bezier else {
+            return;
+        };
+        // splitting at an endpoint would create a degenerate segment
+        if !(constants::EPS..=1.0 - constants::EPS).contains(&t) {
+            return;
+        }
+        let next_i = self.next_i(e_i);
+        let (l, r) = calc::split_cubic_bezier(
+            self.vertices[e_i].p,
+            bezier.control[0],
+            bezier.control[1],
+            self.vertices[next_i].p,
+            t,
+        );
+        self.vertices[e_i].bezier = Some(CubicBezier::new([l[1], l[2]]));
+        let mut joint = Vertex::new(l[3]);
+        joint.bezier = Some(CubicBezier::new([r[1], r[2]]));
+        // the halves are always tangent at the joint, but the lengths of their control
+        // arms are equal only when splitting in the middle, elsewhere the joint is G1
+        // because making it C1 would change the shape of the curve
+        joint.vertex_c = if calc::are_reflections(l[2], r[1], l[3]) {
+            VertexConstraint::C1
+        } else {
+            VertexConstraint::G1
+        };
+        self.vertices.insert(e_i + 1, joint);
+    }
+
+    // inserts a vertex into the edge e_i where it's nearest to p: straight edges get it at
+    // the projection of p, Bézier segments are split there without changing their shape;
+    // a directional constraint holds for both halves so they both keep it, a fixed length
+    // doesn't, so it's dropped; arcs are left as they are
+    // returns whether a vertex was inserted
+    // rollback the change if some constraint was violated
+    pub fn try_insert_vertex(&mut self, e_i: usize, p: Pos2) -> bool {
+        if self.vertices[e_i].arc.is_some() {
+            return false;
+        }
+        let backup = self.vertices.clone();
+        let n = self.vertices.len();
+        let next_i = self.next_i(e_i);
+        if self.vertices[e_i].bezier.is_some() {
+            let t = self.segment(e_i).nearest_parameter(p);
+            self.split_bezier(e_i, t);
+        } else {
+            let (p0, p1) = (self.vertices[e_i].p, self.vertices[next_i].p);
+            let q = calc::project_onto_line(p0, p1, p);
+            // both halves have to be long enough and point the same way as the edge
+            if q.distance(p0) < constants::SIZE_MIN_EDGE_LENGTH
+                || q.distance(p1) < constants::SIZE_MIN_EDGE_LENGTH
+                || (q - p0).dot(p1 - q) <= 0.0
+            {
+                return false;
+            }
+            self.vertices.insert(e_i + 1, Vertex::new(q));
+        }
+        if self.vertices.len() == n {
+            return false;
+        }
+        let edge_c = match self.vertices[e_i].edge_c {
+            Some(EdgeConstraint::FixedLength(_)) => None,
+            edge_c => edge_c,
+        };
+        self.vertices[e_i].edge_c = edge_c;
+        self.vertices[e_i + 1].edge_c = edge_c;
+        // the half next to a smooth joint is shorter than the edge was
+        if !self.resolve_constraints(e_i + 1) {
+            self.vertices = backup;
+            return false;
+        }
+
+        true
+    }
+
+    // replaces the straight edges of the chain from start_i to end_i (the whole
+    // outline if they're the same) with as few Bézier segments as fit its vertices
+    // within the tolerance; the vertices where the new segments meet are kept and
+    // made G1, the ends of the chain become G0 unless they join another curve
+    // rollback the change if some constraint was violated
+    pub fn try_fit_beziers(&mut self, start_i: usize, end_i: usize, tolerance: f64) -> bool {
+        let n = self.vertices.len();
+        let closed = start_i == end_i;
+        let edge_count = if closed { n } else { (end_i + n - start_i) % n };
+        let chain: Vec<usize> = (0..=edge_count).map(|k| (start_i + k) % n).collect();
+        if edge_count < 2
+            || chain[..edge_count]
+                .iter()
+                .any(|&e_i| self.vertices[e_i].bezier.is_some() || self.vertices[e_i].arc.is_some())
+        {
+            return false;
+        }
+
+        let points: Vec<Pos2> = chain.iter().map(|&v_i| self.vertices[v_i].p).collect();
+        let (t0, t1) = if closed {
+            let t = (points[1] - points[edge_count - 1]).normalized();
+            (t, -t)
+        } else {
+            (
+                (points[1] - points[0]).normalized(),
+                (points[edge_count - 1] - points[edge_count]).normalized(),
+            )
+        };
+        let pieces = fit::fit_cubics(&points, t0, t1, tolerance);
+        if pieces.is_empty() {
+            return false;
+        }
+
+        let backup = self.vertices.clone();
+        let mut keep = vec![true; n];
+        for &v_i in chain[1..edge_count].iter() {
+            keep[v_i] = false;
+        }
+        for (k, &(start, [_, c0, c1, _])) in pieces.iter().enumerate() {
+            let v = &mut self.vertices[chain[start]];
+            v.bezier = Some(CubicBezier::new([c0, c1]));
+            v.edge_c = None;
+            if k > 0 {
+                v.vertex_c = VertexConstraint::G1;
+            }
+            keep[chain[start]] = true;
+        }
+        if closed {
+            self.vertices[start_i].vertex_c = VertexConstraint::G1;
+        } else {
+            // a straight edge on the other side of an end would pull the curve towards itself
+            let prev_i = self.prev_i(start_i);
+            if self.vertices[prev_i].bezier.is_none() && self.vertices[prev_i].arc.is_none() {
+                self.vertices[start_i].vertex_c = VertexConstraint::G0;
+            }
+            if self.vertices[end_i].bezier.is_none() && self.vertices[end_i].arc.is_none() {
+                self.vertices[end_i].vertex_c = VertexConstraint::G0;
+            }
+        }
+        let mut v_i = 0;
+        self.vertices.retain(|_| {
+            v_i += 1;
+            keep[v_i - 1]
+        });
+        // a polygon needs at least three vertices
+        while self.vertices.len() < 3 {
+            let Some(e_i) = (0..self.vertices.len())
+                .filter(|&e_i| self.vertices[e_i].bezier.is_some())
+                .max_by(|&a, &b| {
+                    self.segment(a)
+                        .length()
+                        .total_cmp(&self.segment(b).length())
+                })
+            else {
+                break;
+            };
+            self.split_bezier(e_i, 0.5);
+        }
+        if !self.resolve_constraints(0) {
+            self.vertices = backup;
+            return false;
+        }
+
+        true
+    }
+
+    // signed angle by which the outline turns at v_i, positive if it turns clockwise
+    fn turn_angle(&self, v_i: usize) -> f64 {
+        let (prev_i, next_i) = (self.prev_i(v_i), self.next_i(v_i));
+        let t_in = self.vertices[v_i].p - self.vertices[prev_i].p;
+        let t_out = self.vertices[next_i].p - self.vertices[v_i].p;
+
+        calc::cross(t_in, t_out).atan2(t_in.dot(t_out))
+    }
+
+    // a corner can be cut if it's made by two straight edges going different ways
+    pub fn can_cut_corner(&self, v_i: usize) -> bool {
+        let prev_i = self.prev_i(v_i);
+        let is_straight = |v: &Vertex| v.bezier.is_none() && v.arc.is_none();
+        let turn = self.turn_angle(v_i).abs();
+
+        self.vertices.len() >= 3
+            && is_straight(&self.vertices[prev_i])
+            && is_straight(&self.vertices[v_i])
+            && turn > constants::DOT_EPS
+            && turn < std::f64::consts::PI - constants::DOT_EPS
+    }
+
+    // the largest distance from v_i at which its corner can be cut
+    pub fn max_corner_cut(&self, v_i: usize) -> f64 {
+        (self.edge_len(self.prev_i(v_i)).min(self.edge_len(v_i)) - constants::SIZE_MIN_EDGE_LENGTH)
+            .max(0.0)
+    }
+
+    // the radius of the fillet touching the edges at the given distance from v_i
+    pub fn fillet_radius(&self, v_i: usize, distance: f64) -> f64 {
+        distance / (self.turn_angle(v_i).abs() / 2.0).tan()
+    }
+
+    // replaces v_i with two vertices on its edges at the given distance from it,
+    // directional constraints of the edges still hold, but fixed lengths don't;
+    // returns the index of the first new vertex
+    fn cut_corner(&mut self, v_i: usize, distance: f64) -> usize {
+        let (prev_i, next_i) = (self.prev_i(v_i), self.next_i(v_i));
+        let p = self.vertices[v_i].p;
+        let a = p + distance * (self.vertices[prev_i].p - p).normalized();
+        let b = p + distance * (self.vertices[next_i].p - p).normalized();
+        let keep_direction =
+            |c: Option<EdgeConstraint>| c.filter(|c| !matches!(c, EdgeConstraint::FixedLength(_)));
+        self.vertices[prev_i].edge_c = keep_direction(self.vertices[prev_i].edge_c);
+        let mut end = Vertex::new(b);
+        end.edge_c = keep_direction(self.vertices[v_i].edge_c);
+        self.vertices[v_i] = Vertex::new(a);
+        self.vertices.insert(v_i + 1, end);
+
+        v_i
+    }
+
+    // rounds the corner at v_i with an arc of the given radius tangent to both edges
+    // rollback the change if some constraint was violated
+    pub fn try_fillet(&mut self, v_i: usize, radius: f64) -> bool {
+        if !self.can_cut_corner(v_i) {
+            return false;
+        }
+        let turn = self.turn_angle(v_i);
+        let distance = radius * (turn.abs() / 2.0).tan();
+        if distance > self.max_corner_cut(v_i) {
+            return false;
+        }
+        let backup = self.vertices.clone();
+        let start_i = self.cut_corner(v_i, distance);
+        // only one end of an arc can be G1, but the other one
+        // is tangent too since both ends are as far from the corner
+        self.vertices[start_i].arc = Some(CircleArc::new(None, false, turn > 0.0));
+        self.vertices[start_i].vertex_c = VertexConstraint::G1;
+        let end_i = self.next_i(start_i);
+        self.vertices[end_i].vertex_c = VertexConstraint::G0;
+        if !self.resolve_constraints(start_i) {
+            self.vertices = backup;
+            return false;
+        }
+
+        true
+    }
+
+    // cuts the corner at v_i with a straight edge between the points
+    // at the given distance from it along both edges
+    // rollback the change if some constraint was violated
+    pub fn try_chamfer(&mut self, v_i: usize, distance: f64) -> bool {
+        if !self.can_cut_corner(v_i) || distance > self.max_corner_cut(v_i) {
+            return false;
+        }
+        let backup = self.vertices.clone();
+        let start_i = self.cut_corner(v_i, distance);
+        if !self.resolve_constraints(start_i) {
+            self.vertices = backup;
+            return false;
+        }
+
+        true
+    }
+
+    // initial positions for the control points
+    // of a Bézier curve replacing the edge e_i
+    fn init_bezier_control_points(&self, e_i: usize) -> [Pos2; 2] {
+        let c0 = self.vertices[e_i].p
+            + (1.0 / 3.0) * (self.vertices[e_i].p - self.vertices[self.prev_i(e_i)].p);
+        let next_i = self.next_i(e_i);
+        let c1 = self.vertices[next_i].p
+            + (1.0 / 3.0) * (self.vertices[next_i].p - self.vertices[self.next_i(next_i)].p);
+
+        [c0, c1]
+    }
+
+    pub fn init_bezier(&mut self, e_i: usize) {
+        let control = self.init_bezier_control_points(e_i);
+        self.vertices[e_i].bezier = Some(CubicBezier::new(control));
+    }
+
+    fn resolve_g1(
+        &mut self,
+        free_i: usize,
+        fixed_i: usize,
+        control_p: Pos2,
+        edge_c: Option<EdgeConstraint>,
+    ) {
+        // in general we don't want to move fixed_i, but
+        // we need to if the angle is constrained
+        match edge_c {
+            Some(EdgeConstraint::Vertical) => self.vertices[fixed_i].p.x = control_p.x,
+            Some(EdgeConstraint::Horizontal) => self.vertices[fixed_i].p.y = control_p.y,
+            Some(EdgeConstraint::DiagonalUp) => {
+                self.vertices[fixed_i].p =
+                    calc::project_onto_diagonal_up(control_p, self.vertices[fixed_i].p)
+            }
+            Some(EdgeConstraint::DiagonalDown) => {
+                self.vertices[fixed_i].p =
+                    calc::project_onto_diagonal_down(control_p, self.vertices[fixed_i].p)
+            }
+            _ => (),
+        }
+        self.vertices[free_i].p =
+            calc::keep_g1(self.vertices[fixed_i].p, self.vertices[free_i].p, control_p);
+    }
+
+    // resolves G1 continuity in a joint between Bezier curves
+    // (control points need to be colinear with the joint)
+    fn resolve_g1_joint(&mut self, v_i: usize, which: usize, joint_i: usize, control_p: Pos2) {
+        let joint_p = self.vertices[joint_i].p;
+        if let Some(bezier) = &mut self.vertices[v_i].bezier {
+            bezier.control[which] =
+                calc::project_onto_line(joint_p, control_p, bezier.control[which])
+        }
+    }
+
+    fn resolve_c1_joint(&mut self, v_i: usize, which: usize, joint_i: usize, control_p: Pos2) {
+        let joint_p = self.vertices[joint_i].p;
+        if let Some(bezier) = &mut self.vertices[v_i].bezier {
+            bezier.control[which] = calc::reflection(joint_p, control_p);
+        }
+    }
+
+    fn resolve_c1(
+        &mut self,
+        free_i: usize,
+        fixed_i: usize,
+        control_p: Pos2,
+        edge_c: Option<EdgeConstraint>,
+    ) -> Pos2 {
+        let mut resolved_p = control_p;
+        match edge_c {
+            Some(EdgeConstraint::Vertical) => self.vertices[fixed_i].p.x = control_p.x,
+            Some(EdgeConstraint::Horizontal) => self.vertices[fixed_i].p.y = control_p.y,
+            Some(EdgeConstraint::DiagonalUp) => {
+                self.vertices[fixed_i].p =
+                    calc::project_onto_diagonal_up(control_p, self.vertices[fixed_i].p)
+            }
+            Some(EdgeConstraint::DiagonalDown) => {
+                self.vertices[fixed_i].p =
+                    calc::project_onto_diagonal_down(control_p, self.vertices[fixed_i].p)
+            }
+            // if we have a length constraint on the edge, the control vertex can only be
+            // in one place
+            Some(EdgeConstraint::FixedLength(len)) => {
+                resolved_p = calc::rescale(self.vertices[fixed_i].p, control_p, len / 3.0);
+            }
+            _ => (),
+        }
+        self.vertices[free_i].p = calc::keep_c1(self.vertices[fixed_i].p, resolved_p);
+
+        resolved_p
+    }
+
+    pub fn resolve_bezier_constraints(&mut self, v_i: usize, which: usize, new_p: Pos2) -> bool {
+        let prev_i = self.prev_i(v_i);
+        let next_i = self.next_i(v_i);
+        let next_next_i = self.next_i(next_i);
+        let vertex_c = match which {
+            0 => self.vertices[v_i].vertex_c,
+            1 => self.vertices[next_i].vertex_c,
+            _ => unreachable!(),
+        };
+        match vertex_c {
+            // control point can be wherever
+            VertexConstraint::G0 => {
+                self.vertices[v_i].move_bezier_control_vertex(which, new_p);
+                return true;
+            }
+            // control point needs to be colinear with the edge that leads into it
+            // (curvature continuity is taken care of by the resolver)
+            VertexConstraint::G1 | VertexConstraint::G2 => {
+                self.vertices[v_i].move_bezier_control_vertex(which, new_p);
+                match which {
+                    0 => {
+                        if self.is_bezier_start(prev_i) {
+                            self.resolve_g1_joint(prev_i, 1, v_i, new_p);
+                        } else {
+                            self.resolve_g1(prev_i, v_i, new_p, self.vertices[prev_i].edge_c);
+                        }
+                    }
+                    1 => {
+                        if self.is_bezier_start(next_i) {
+                            self.resolve_g1_joint(next_i, 0, next_i, new_p);
+                        } else {
+                            self.resolve_g1(
+                                next_next_i,
+                                next_i,
+                                new_p,
+                                self.vertices[next_i].edge_c,
+                            );
+                        }
+                    }
+                    _ => unreachable!(),
+                };
+            }
+            // control point needs to be colinear with the edge that leads into it
+            // and dist(p1, control point) = 1/3 * dist(p0, p1)
+            VertexConstraint::C1 | VertexConstraint::C2 => match which {
+                0 => {
+                    if self.is_bezier_start(prev_i) {
+                        self.resolve_c1_joint(prev_i, 1, v_i, new_p);
+                    } else {
+                        let edge_c = self.vertices[prev_i].edge_c;
+                        let resolved_p = self.resolve_c1(prev_i, v_i, new_p, edge_c);
+                        self.vertices[v_i].move_bezier_control_vertex(0, resolved_p);
+                    }
+                }
+                1 => {
+                    if self.is_bezier_start(next_i) {
+                        self.resolve_c1_joint(next_i, 0, next_i, new_p);
+                    } else {
+                        let edge_c = self.vertices[next_i].edge_c;
+                        let resolved_p = self.resolve_c1(next_next_i, next_i, new_p, edge_c);
+                        self.vertices[v_i].move_bezier_control_vertex(1, resolved_p);
+                    }
+                }
+                _ => unreachable!(),
+            },
+        }
+
+        self.resolve_constraints(v_i)
+    }
+
+    // try to move the control point `which` of the Bézier edge v_i to new_p
+    // returns false and rolls back the move
+    // if it violated some constraint
+    pub fn try_move_control_vertex(&mut self, v_i: usize, which: usize, new_p: Pos2) -> bool {
+        let backup = self.vertices.clone();
+        if self.resolve_bezier_constraints(v_i, which, new_p) && self.is_valid_after_move(v_i) {
+            true
+        } else {
+            self.vertices = backup;
+            false
+        }
+    }
+
+    pub fn make_arc(&mut self, e_i: usize) {
+        use VertexConstraint as VC;
+
+        self.vertices[e_i].arc = Some(CircleArc::default());
+        let next_i = self.next_i(e_i);
+        for v_i in [e_i, next_i] {
+            if is_curvature_continuous(self.vertices[v_i].vertex_c) {
+                self.vertices[v_i].vertex_c = VC::C1;
+            }
+        }
+        // only G0/G1 is allowed with arcs, and at most one end can be G1
+        match (self.vertices[e_i].vertex_c, self.vertices[next_i].vertex_c) {
+            (VC::C1, VC::C1) | (VC::C1, VC::G1) | (VC::G1, VC::C1) => {
+                self.vertices[e_i].vertex_c = VC::G1;
+                self.vertices[next_i].vertex_c = VC::G0;
+            }
+            (VC::G1, _) => {
+                self.vertices[next_i].vertex_c = VC::G0;
+            }
+            (_, VC::G1) => {
+                self.vertices[e_i].vertex_c = VC::G0;
+            }
+            _ => (),
+        }
+    }
+
+    // set the radius and direction of the arc e_i
+    // rollback the change if some constraint was violated
+    pub fn try_set_arc(&mut self, e_i: usize, arc: CircleArc) {
+        let backup = self.vertices.clone();
+        self.vertices[e_i].arc = Some(arc);
+        if !self.resolve_constraints(e_i) {
+            self.vertices = backup;
+        }
+    }
+
+    // index of the arc whose midpoint handle is near p
+    pub fn nearby_arc_handle(&self, p: Pos2) -> Option<usize> {
+        (0..self.vertices.len()).find(|&e_i| {
+            self.can_set_arc_radius(e_i)
+                && self.arc_data(e_i).midpoint().distance_sq(p)
+                    <= constants::SIZE_CONTROL_VERTEX * constants::SIZE_CONTROL_VERTEX
+        })
+    }
+
+    // reshape the arc e_i so that it goes through p
+    pub fn try_move_arc_handle(&mut self, e_i: usize, p: Pos2) {
+        let next_i = self.next_i(e_i);
+        if let Some((r, large, clockwise)) =
+            calc::arc_through_point(self.vertices[e_i].p, self.vertices[next_i].p, p)
+        {
+            self.try_set_arc(e_i, CircleArc::new(Some(r), large, clockwise));
+        }
+    }
+
+    pub fn can_be_g1(&self, v_i: usize) -> bool {
+        let next_i = self.next_i(v_i);
+        let prev_i = self.prev_i(v_i);
+
+        if self.has_arc_radius(v_i) || self.has_arc_radius(prev_i) {
+            return false;
+        }
+
+        !(self.is_arc_start(v_i) || self.is_arc_end(v_i))
+            || (self.is_arc_start(v_i)
+                && matches!(self.vertices[next_i].vertex_c, VertexConstraint::G0))
+            || (self.is_arc_end(v_i)
+                && matches!(self.vertices[prev_i].vertex_c, VertexConstraint::G0))
+    }
+
+    pub fn can_be_c1(&self, v_i: usize) -> bool {
+        !(self.is_arc_start(v_i) || self.is_arc_end(v_i))
+    }
+
+    // curvature continuity makes sense only between two Bézier segments
+    pub fn can_be_g2(&self, v_i: usize) -> bool {
+        self.is_bezier_start(v_i) && self.is_bezier_start(self.prev_i(v_i))
+    }
+
+    pub fn can_be_c2(&self, v_i: usize) -> bool {
+        self.can_be_g2(v_i)
+    }
+}
+
+// the directional constraint satisfied by an edge going along d, if any
+fn direction_constraint(d: Vec2) -> Option<EdgeConstraint> {
+    let tolerance = constants::DOT_EPS * d.length();
+    if d.x.abs() < tolerance {
+        Some(EdgeConstraint::Vertical)
+    } else if d.y.abs() < tolerance {
+        Some(EdgeConstraint::Horizontal)
+    } else if (d.x + d.y).abs() < tolerance {
+        Some(EdgeConstraint::DiagonalUp)
+    } else if (d.x - d.y).abs() < tolerance {
+        Some(EdgeConstraint::DiagonalDown)
+    } else {
+        None
+    }
+}
+
+fn enforce_continuity_constraint(p0: Pos2, p1: Pos2, control: Pos2, c: VertexConstraint) -> Pos2 {
+    match c {
+        VertexConstraint::G0 => control,
+        VertexConstraint::G1 | VertexConstraint::G2 => calc::enforce_g1(p0, p1, control),
+        VertexConstraint::C1 | VertexConstraint::C2 => calc::enforce_c1(p0, p1),
+    }
+}
+
+fn enforce_joint_continuity(
+    control_p: Pos2,
+    other_control_p: Pos2,
+    v: Pos2,
+    c: VertexConstraint,
+) -> Pos2 {
+    match c {
+        VertexConstraint::G0 => control_p,
+        VertexConstraint::G1 | VertexConstraint::G2 => {
+            calc::project_onto_line(v, other_control_p, control_p)
+        }
+        VertexConstraint::C1 | VertexConstraint::C2 => calc::reflection(v, other_control_p),
+    }
+}
+
+fn check_continuity_constraint(p0: Pos2, p1: Pos2, control: Pos2, c: VertexConstraint) -> bool {
+    match c {
+        VertexConstraint::G0 => true,
+        VertexConstraint::G1 | VertexConstraint::G2 => calc::check_g1(p0, p1, control),
+        VertexConstraint::C1 | VertexConstraint::C2 => calc::check_c1(p0, p1, control),
+    }
+}
+
+fn check_joint_continuity(
+    control_p1: Pos2,
+    control_p2: Pos2,
+    v: Pos2,
+    c: VertexConstraint,
+) -> bool {
+    match c {
+        VertexConstraint::G0 => true,
+        VertexConstraint::G1 | VertexConstraint::G2 => {
+            calc::are_colinear(control_p1, control_p2, v)
+        }
+        VertexConstraint::C1 | VertexConstraint::C2 => {
+            calc::are_reflections(control_p1, control_p2, v)
+        }
+    }
+}
+
+fn is_curvature_continuous(c: VertexConstraint) -> bool {
+    matches!(c, VertexConstraint::G2 | VertexConstraint::C2)
+}
+
+// returns the new position of the second control point of the curve
+// after the joint v, given the control points of both curves
+fn enforce_curvature_continuity(
+    prev_control: [Pos2; 2],
+    v: Pos2,
+    control: [Pos2; 2],
+    c: VertexConstraint,
+) -> Pos2 {
+    match c {
+        VertexConstraint::G2 => {
+            calc::enforce_g2(prev_control[0], prev_control[1], v, control[0], control[1])
+        }
+        VertexConstraint::C2 => calc::enforce_c2(prev_control[0], prev_control[1], control[0]),
+        _ => control[1],
+    }
+}
+
+fn check_curvature_continuity(
+    prev_control: [Pos2; 2],
+    v: Pos2,
+    control: [Pos2; 2],
+    c: VertexConstraint,
+) -> bool {
+    enforce_curvature_continuity(prev_control, v, control, c).distance(control[1])
+        < constants::CONSTRAINT_DIST_TOLERANCE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> Polygon {
+        Polygon::from_points(
+            &[
+                Pos2::new(0.0, 0.0),
+                Pos2::new(100.0, 0.0),
+                Pos2::new(100.0, 100.0),
+                Pos2::new(0.0, 100.0),
+            ],
+            false,
+        )
+    }
+
+    fn all_satisfied(polygon: &Polygon) -> bool {
+        (0..polygon.vertices.len()).all(|v_i| polygon.check_constraint(v_i))
+    }
+
+    #[test]
+    fn apply_constraint_satisfies_edge_constraints() {
+        for edge_c in [
+            EdgeConstraint::Vertical,
+            EdgeConstraint::Horizontal,
+            EdgeConstraint::DiagonalUp,
+            EdgeConstraint::DiagonalDown,
+            EdgeConstraint::FixedLength(42.0),
+        ] {
+            let mut polygon = square();
+            polygon.vertices[1].p = Pos2::new(80.0, 30.0);
+            polygon.vertices[0].edge_c = Some(edge_c);
+            assert!(!polygon.check_constraint(0), "{edge_c:?}");
+            polygon.apply_constraint(0);
+            assert!(polygon.check_constraint(0), "{edge_c:?}");
+            // only the end of the edge moves
+            assert_eq!(polygon.vertices[0].p, Pos2::new(0.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn apply_constraint_shortens_chords_of_arcs_with_a_radius() {
+        let mut polygon = square();
+        polygon.vertices[0].arc = Some(CircleArc::new(Some(30.0), false, true));
+        assert!(!polygon.check_constraint(0));
+        polygon.apply_constraint(0);
+        assert!(polygon.check_constraint(0));
+        assert!((polygon.edge_len(0) - 60.0).abs() < constants::CONSTRAINT_DIST_TOLERANCE);
+    }
+
+    #[test]
+    fn apply_constraint_enforces_continuity_of_bezier_curves() {
+        for vertex_c in [VertexConstraint::G1, VertexConstraint::C1] {
+            let mut polygon = square();
+            polygon.vertices[1].bezier = Some(CubicBezier::new([
+                Pos2::new(130.0, 20.0),
+                Pos2::new(130.0, 110.0),
+            ]));
+            polygon.vertices[1].vertex_c = vertex_c;
+            polygon.vertices[2].vertex_c = vertex_c;
+            assert!(!polygon.check_constraint(1), "{vertex_c:?}");
+            polygon.apply_constraint(1);
+            assert!(polygon.check_constraint(1), "{vertex_c:?}");
+        }
+    }
+
+    #[test]
+    fn try_set_vertex_constraint_adjusts_the_curve_with_a_free_end() {
+        // the curve before the joint has its other end free and the one after doesn't
+        for vertex_c in [VertexConstraint::G2, VertexConstraint::C2] {
+            let mut polygon = square();
+            polygon.vertices[0].bezier = Some(CubicBezier::new([
+                Pos2::new(30.0, -30.0),
+                Pos2::new(80.0, -20.0),
+            ]));
+            polygon.vertices[1].bezier = Some(CubicBezier::new([
+                Pos2::new(120.0, 20.0),
+                Pos2::new(130.0, 70.0),
+            ]));
+            polygon.vertices[0].vertex_c = VertexConstraint::G0;
+            assert!(polygon.resolve_constraints(1));
+            polygon.try_set_vertex_constraint(1, vertex_c);
+            assert_eq!(polygon.vertices[1].vertex_c, vertex_c);
+            assert_eq!(polygon.vertices[2].vertex_c, VertexConstraint::C1);
+            assert!(all_satisfied(&polygon), "{vertex_c:?}");
+        }
+    }
+
+    #[test]
+    fn resolve_constraints_satisfies_compatible_constraints() {
+        let mut polygon = square();
+        polygon.vertices[1].p = Pos2::new(120.0, 15.0);
+        polygon.vertices[2].p = Pos2::new(90.0, 140.0);
+        polygon.vertices[0].edge_c = Some(EdgeConstraint::Horizontal);
+        polygon.vertices[1].edge_c = Some(EdgeConstraint::Vertical);
+        polygon.vertices[2].edge_c = Some(EdgeConstraint::FixedLength(50.0));
+        polygon.vertices[3].edge_c = Some(EdgeConstraint::DiagonalDown);
+        assert!(polygon.resolve_constraints(0));
+        assert!(all_satisfied(&polygon));
+    }
+
+    #[test]
+    fn resolve_constraints_fails_on_contradicting_constraints() {
+        // no triangle has sides of these lengths
+        let mut polygon = Polygon::from_points(
+            &[
+                Pos2::new(0.0, 0.0),
+                Pos2::new(100.0, 0.0),
+                Pos2::new(50.0, 80.0),
+            ],
+            false,
+        );
+        for (v, len) in polygon.vertices.iter_mut().zip([10.0, 10.0, 100.0]) {
+            v.edge_c = Some(EdgeConstraint::FixedLength(len));
+        }
+        assert!(!polygon.resolve_constraints(0));
+        assert!(!all_satisfied(&polygon));
+    }
+
+    #[test]
+    fn try_move_vertex_keeps_constraints() {
+        let mut polygon = square();
+        polygon.vertices[0].edge_c = Some(EdgeConstraint::Horizontal);
+        polygon.vertices[1].edge_c = Some(EdgeConstraint::Vertical);
+        assert!(polygon.try_move_vertex(0, Pos2::new(20.0, 30.0)));
+        assert!(all_satisfied(&polygon));
+        assert_eq!(polygon.vertices[0].p, Pos2::new(20.0, 30.0));
+    }
+
+    #[test]
+    fn try_move_vertex_rolls_back_impossible_moves() {
+        let mut polygon = Polygon::from_points(
+            &[
+                Pos2::new(0.0, 0.0),
+                Pos2::new(100.0, 0.0),
+                Pos2::new(50.0, 80.0),
+            ],
+            false,
+        );
+        for (v, len) in polygon.vertices.iter_mut().zip([10.0, 10.0, 100.0]) {
+            v.edge_c = Some(EdgeConstraint::FixedLength(len));
+        }
+        let before = polygon.vertices.clone();
+        assert!(!polygon.try_move_vertex(0, Pos2::new(20.0, 30.0)));
+        assert_eq!(polygon.vertices, before);
+    }
+
+    #[test]
+    fn try_move_vertex_rolls_back_self_intersections() {
+        let mut polygon = square();
+        polygon.reject_self_intersections = true;
+        let before = polygon.vertices.clone();
+        assert!(!polygon.try_move_vertex(0, Pos2::new(150.0, 50.0)));
+        assert_eq!(polygon.vertices, before);
+        polygon.reject_self_intersections = false;
+        assert!(polygon.try_move_vertex(0, Pos2::new(150.0, 50.0)));
+    }
+
+    #[test]
+    fn split_bezier_keeps_the_joint_smooth() {
+        for (t, vertex_c) in [(0.5, VertexConstraint::C1), (0.3, VertexConstraint::G1)] {
+            let mut polygon = square();
+            polygon.vertices[0].bezier = Some(CubicBezier::new([
+                Pos2::new(30.0, -40.0),
+                Pos2::new(70.0, -40.0),
+            ]));
+            // G1 at the ends still holds when the control arms get shorter
+            polygon.vertices[0].vertex_c = VertexConstraint::G1;
+            polygon.vertices[1].vertex_c = VertexConstraint::G1;
+            assert!(polygon.resolve_constraints(0));
+            polygon.split_bezier(0, t);
+            assert_eq!(polygon.vertices.len(), 5);
+            assert_eq!(polygon.vertices[1].vertex_c, vertex_c, "t = {t}");
+            assert!(all_satisfied(&polygon), "t = {t}");
+        }
+    }
+
+    #[test]
+    fn try_insert_vertex_keeps_directional_constraints() {
+        let mut polygon = square();
+        polygon.vertices[0].edge_c = Some(EdgeConstraint::Horizontal);
+        polygon.vertices[1].edge_c = Some(EdgeConstraint::FixedLength(100.0));
+        assert!(polygon.try_insert_vertex(0, Pos2::new(30.0, 5.0)));
+        assert_eq!(polygon.vertices[1].p, Pos2::new(30.0, 0.0));
+        assert_eq!(polygon.vertices[0].edge_c, Some(EdgeConstraint::Horizontal));
+        assert_eq!(polygon.vertices[1].edge_c, Some(EdgeConstraint::Horizontal));
+        assert!(polygon.try_insert_vertex(2, Pos2::new(90.0, 40.0)));
+        assert_eq!(polygon.vertices[2].edge_c, None);
+        assert_eq!(polygon.vertices[3].edge_c, None);
+        assert_eq!(polygon.vertices.len(), 6);
+        assert!(all_satisfied(&polygon));
+        // not past the ends of the edge
+        assert!(!polygon.try_insert_vertex(0, Pos2::new(-20.0, 0.0)));
+    }
+
+    #[test]
+    fn try_insert_vertex_splits_bezier_segments_in_place() {
+        let mut polygon = square();
+        polygon.vertices[0].bezier = Some(CubicBezier::new([
+            Pos2::new(30.0, -40.0),
+            Pos2::new(70.0, -40.0),
+        ]));
+        // G1 at the ends still holds when the control arms get shorter
+        polygon.vertices[0].vertex_c = VertexConstraint::G1;
+        polygon.vertices[1].vertex_c = VertexConstraint::G1;
+        assert!(polygon.resolve_constraints(0));
+        let segment = polygon.segment(0);
+        let p = segment.point_at(0.3);
+        assert!(polygon.try_insert_vertex(0, p + Vec2::new(0.0, -1.0)));
+        assert!(polygon.vertices[1].p.distance(p) < 1.0);
+        let (left, right) = (polygon.segment(0), polygon.segment(1));
+        for t in [0.25, 0.5, 0.75] {
+            let q = left.point_at(t);
+            assert!(q.distance(segment.point_at(segment.nearest_parameter(q))) < 1e-6);
+            let q = right.point_at(t);
+            assert!(q.distance(segment.point_at(segment.nearest_parameter(q))) < 1e-6);
+        }
+    }
+
+    #[test]
+    fn try_insert_vertex_keeps_smooth_joints() {
+        for vertex_c in [VertexConstraint::G1, VertexConstraint::C1] {
+            let mut polygon = square();
+            polygon.vertices[0].bezier = Some(CubicBezier::new([
+                Pos2::new(30.0, -40.0),
+                Pos2::new(70.0, -40.0),
+            ]));
+            polygon.vertices[1].vertex_c = vertex_c;
+            assert!(polygon.resolve_constraints(1));
+            assert!(polygon.try_insert_vertex(1, Pos2::new(100.0, 20.0)));
+            assert_eq!(polygon.vertices.len(), 5);
+            assert!(all_satisfied(&polygon), "{vertex_c:?}");
+        }
+    }
+
+    #[test]
+    fn try_move_edge_keeps_its_shape() {
+        let mut polygon = square();
+        polygon.vertices[0].edge_c = Some(EdgeConstraint::FixedLength(100.0));
+        assert!(polygon.try_move_edge(0, Vec2::new(7.0, 3.0)));
+        assert_eq!(polygon.vertices[0].p, Pos2::new(7.0, 3.0));
+        assert_eq!(polygon.vertices[1].p, Pos2::new(107.0, 3.0));
+        polygon.vertices[1].edge_c = Some(EdgeConstraint::Vertical);
+        polygon.vertices[3].edge_c = Some(EdgeConstraint::Vertical);
+        assert!(polygon.try_move_edge(0, Vec2::new(7.0, 3.0)));
+        assert!(all_satisfied(&polygon));
+    }
+
+    #[test]
+    fn try_transform_keeps_arcs_circular() {
+        let mut polygon = square();
+        polygon.vertices[0].arc = Some(CircleArc::new(Some(80.0), false, true));
+        assert!(polygon.resolve_constraints(0));
+        let before = polygon.vertices.clone();
+        let pivot = Pos2::new(50.0, 50.0);
+        assert!(!polygon.try_transform(Transform::scale(pivot, 2.0, 1.0), true));
+        assert_eq!(polygon.vertices, before);
+        assert!(polygon.try_transform(Transform::scale(pivot, 2.0, 2.0), true));
+        assert_eq!(polygon.vertices[0].arc.unwrap().radius, Some(160.0));
+        assert!(polygon.try_transform(Transform::rotation(pivot, 1.0), true));
+    }
+}
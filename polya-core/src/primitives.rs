@@ -0,0 +1,189 @@
+use std::f64::consts;
+
+use crate::{
+    calc::ArcData,
+    constants,
+    math::{Pos2, Rect, Vec2},
+    polygon::Polygon,
+    segment::Segment,
+    vertex::{EdgeConstraint, VertexConstraint},
+};
+
+// what circles are made of
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CurveKind {
+    #[default]
+    Arc,
+    Bezier,
+}
+
+// shapes dragged out with the mouse, all going clockwise on the screen
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Primitive {
+    // dragged from corner to corner
+    Rectangle,
+    // dragged from the center to a vertex, with the number of sides
+    RegularPolygon(usize),
+    // dragged from the center to a point on the circle
+    Circle(CurveKind),
+    // dragged from corner to corner, with the radius of the corners
+    RoundedRectangle(f64),
+}
+
+// straight edges get constrained to stay horizontal or vertical
+fn constrain_axis_aligned(polygon: &mut Polygon) {
+    let n = polygon.vertices.len();
+    for e_i in 0..n {
+        if !matches!(polygon.segment(e_i), Segment::Line(_)) {
+            continue;
+        }
+        let d = polygon.vertices[(e_i + 1) % n].p - polygon.vertices[e_i].p;
+        polygon.vertices[e_i].edge_c = if d.y.abs() < constants::EPS {
+            Some(EdgeConstraint::Horizontal)
+        } else if d.x.abs() < constants::EPS {
+            Some(EdgeConstraint::Vertical)
+        } else {
+            None
+        };
+    }
+}
+
+fn rectangle(rect: Rect) -> Polygon {
+    let corners = [
+        rect.left_top(),
+        rect.right_top(),
+        rect.right_bottom(),
+        rect.left_bottom(),
+    ];
+    let mut polygon = Polygon::from_points(&corners, false);
+    constrain_axis_aligned(&mut polygon);
+
+    polygon
+}
+
+fn regular_polygon(center: Pos2, corner: Pos2, sides: usize) -> Polygon {
+    let (radius, angle) = ((corner - center).length(), (corner - center).angle());
+    let points: Vec<Pos2> = (0..sides)
+        .map(|k| center + radius * Vec2::angled(angle + k as f64 * consts::TAU / sides as f64))
+        .collect();
+    let mut polygon = Polygon::from_points(&points, false);
+    let len = polygon.edge_len(0);
+    if len >= constants::SIZE_MIN_EDGE_LENGTH {
+        for v in polygon.vertices.iter_mut() {
+            v.edge_c = Some(EdgeConstraint::FixedLength(len));
+        }
+    }
+
+    polygon
+}
+
+fn circle(center: Pos2, radius: f64, kind: CurveKind) -> Polygon {
+    let quarter = |k: usize| ArcData {
+        center,
+        radius,
+        start_angle: k as f64 * consts::FRAC_PI_2,
+        sweep: consts::FRAC_PI_2,
+    };
+    match kind {
+        CurveKind::Arc => {
+            let arcs: Vec<Segment> = (0..4).map(|k| Segment::Arc(quarter(k))).collect();
+            Polygon::from_segments(&arcs, false)
+        }
+        CurveKind::Bezier => {
+            // the handle length for which the midpoint of the curve lies on the circle
+            let handle = 4.0 / 3.0 * (consts::FRAC_PI_8).tan() * radius;
+            let cubics: Vec<Segment> = (0..4)
+                .map(|k| {
+                    let arc = quarter(k);
+                    let (p0, p1) = (
+                        arc.point_at(arc.start_angle),
+                        arc.point_at(arc.start_angle + arc.sweep),
+                    );
+                    let (t0, t1) = (
+                        Vec2::angled(arc.start_angle + consts::FRAC_PI_2),
+                        Vec2::angled(arc.start_angle + arc.sweep + consts::FRAC_PI_2),
+                    );
+                    Segment::Cubic([p0, p0 + handle * t0, p1 - handle * t1, p1])
+                })
+                .collect();
+            let mut polygon = Polygon::from_segments(&cubics, false);
+            // all handles have the same length, so the joints are C1
+            for v in polygon.vertices.iter_mut() {
+                v.vertex_c = VertexConstraint::C1;
+            }
+            polygon
+        }
+    }
+}
+
+fn rounded_rectangle(rect: Rect, radius: f64) -> Polygon {
+    let r = radius.min(rect.width() / 2.0).min(rect.height() / 2.0);
+    if r < constants::EPS {
+        return rectangle(rect);
+    }
+    let corner = |center: Pos2, k: usize| {
+        Segment::Arc(ArcData {
+            center,
+            radius: r,
+            start_angle: k as f64 * consts::FRAC_PI_2 - consts::PI,
+            sweep: consts::FRAC_PI_2,
+        })
+    };
+    let inner = rect.shrink(r);
+    let segments = [
+        corner(inner.left_top(), 0),
+        Segment::Line([
+            rect.left_top() + Vec2::X * r,
+            rect.right_top() - Vec2::X * r,
+        ]),
+        corner(inner.right_top(), 1),
+        Segment::Line([
+            rect.right_top() + Vec2::Y * r,
+            rect.right_bottom() - Vec2::Y * r,
+        ]),
+        corner(inner.right_bottom(), 2),
+        Segment::Line([
+            rect.right_bottom() - Vec2::X * r,
+            rect.left_bottom() + Vec2::X * r,
+        ]),
+        corner(inner.left_bottom(), 3),
+        Segment::Line([
+            rect.left_bottom() - Vec2::Y * r,
+            rect.left_top() + Vec2::Y * r,
+        ]),
+    ];
+    // with the largest radius some sides shrink to nothing
+    let segments: Vec<Segment> = segments
+        .into_iter()
+        .filter(|segment| segment.length() >= constants::EPS)
+        .collect();
+    let mut polygon = Polygon::from_segments(&segments, false);
+    constrain_axis_aligned(&mut polygon);
+
+    polygon
+}
+
+impl Primitive {
+    // the shape dragged out from `from` to `to`, None if it's too small
+    pub fn build(self, from: Pos2, to: Pos2) -> Option<Polygon> {
+        let rect = Rect::from_two_pos(from, to);
+        let too_small = match self {
+            Primitive::Rectangle | Primitive::RoundedRectangle(_) => {
+                rect.width().min(rect.height()) < constants::SIZE_MIN_PRIMITIVE
+            }
+            Primitive::RegularPolygon(_) | Primitive::Circle(_) => {
+                from.distance(to) < constants::SIZE_MIN_PRIMITIVE
+            }
+        };
+        if too_small {
+            return None;
+        }
+
+        Some(match self {
+            Primitive::Rectangle => rectangle(rect),
+            Primitive::RegularPolygon(sides) => regular_polygon(from, to, sides.max(3)),
+            Primitive::Circle(kind) => circle(from, from.distance(to), kind),
+            Primitive::RoundedRectangle(radius) => rounded_rectangle(rect, radius),
+        })
+    }
+}
@@ -0,0 +1,41 @@
+use crate::{
+    constants,
+    math::{Pos2, Rect, Vec2},
+    polygon::Polygon,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Properties {
+    // positive if the outline goes clockwise on the screen (the y axis points down)
+    pub signed_area: f64,
+    pub perimeter: f64,
+    pub centroid: Pos2,
+    pub bounding_box: Rect,
+}
+
+impl Properties {
+    pub fn of(polygon: &Polygon) -> Self {
+        let mut signed_area = 0.0;
+        let mut perimeter = 0.0;
+        let mut moments = Vec2::ZERO;
+        let mut bounding_box = Rect::NOTHING;
+        for segment in polygon.segments() {
+            signed_area += segment.area();
+            perimeter += segment.length();
+            moments += segment.moments();
+            bounding_box = bounding_box.union(segment.bounding_box());
+        }
+        let centroid = if signed_area.abs() < constants::EPS {
+            bounding_box.center()
+        } else {
+            (moments / signed_area).to_pos2()
+        };
+
+        Self {
+            signed_area,
+            perimeter,
+            centroid,
+            bounding_box,
+        }
+    }
+}
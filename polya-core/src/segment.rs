@@ -0,0 +1,279 @@
+use std::f64::consts;
+
+use crate::{
+    calc::{self, ArcData},
+    constants,
+    math::{Pos2, Rect, Vec2},
+};
+
+// geometry of a single edge of a polygon
+#[derive(Clone, Copy, Debug)]
+pub enum Segment {
+    Line([Pos2; 2]),
+    // start, first control point, second control point, end
+    Cubic([Pos2; 4]),
+    Arc(ArcData),
+}
+
+impl Segment {
+    pub fn start(&self) -> Pos2 {
+        match self {
+            Segment::Line([p0, _]) | Segment::Cubic([p0, _, _, _]) => *p0,
+            Segment::Arc(arc) => arc.point_at(arc.start_angle),
+        }
+    }
+
+    pub fn end(&self) -> Pos2 {
+        match self {
+            Segment::Line([_, p1]) | Segment::Cubic([_, _, _, p1]) => *p1,
+            Segment::Arc(arc) => arc.point_at(arc.start_angle + arc.sweep),
+        }
+    }
+
+    // t goes from 0 at the start to 1 at the end
+    pub fn point_at(&self, t: f64) -> Pos2 {
+        match self {
+            Segment::Line([p0, p1]) => p0.lerp(*p1, t),
+            Segment::Cubic([p0, c0, c1, p1]) => calc::cubic_bezier_point(*p0, *c0, *c1, *p1, t),
+            Segment::Arc(arc) => arc.point_at(arc.start_angle + t * arc.sweep),
+        }
+    }
+
+    // unit tangent at parameter t, in the direction of travel
+    pub fn tangent(&self, t: f64) -> Vec2 {
+        let d = match self {
+            Segment::Line([p0, p1]) => *p1 - *p0,
+            Segment::Cubic([p0, c0, c1, p1]) => {
+                calc::cubic_bezier_derivatives(*p0, *c0, *c1, *p1, t).0
+            }
+            Segment::Arc(arc) => {
+                arc.radius
+                    * arc.sweep
+                    * Vec2::angled(arc.start_angle + t * arc.sweep + consts::FRAC_PI_2)
+            }
+        };
+        if d.length() >= constants::EPS {
+            return d.normalized();
+        }
+        // the derivative vanishes where a control point coincides with an endpoint,
+        // but the direction of the curve there is still well defined
+        let dt = constants::EPS;
+        (self.point_at((t + dt).min(1.0)) - self.point_at((t - dt).max(0.0))).normalized()
+    }
+
+    // parameter of the point on the segment nearest to p
+    pub fn nearest_parameter(&self, p: Pos2) -> f64 {
+        match self {
+            Segment::Line([p0, p1]) => {
+                let d = *p1 - *p0;
+                if d.length_sq() < constants::EPS * constants::EPS {
+                    return 0.0;
+                }
+                ((p - *p0).dot(d) / d.length_sq()).clamp(0.0, 1.0)
+            }
+            Segment::Cubic([p0, c0, c1, p1]) => {
+                calc::nearest_cubic_bezier_parameter(*p0, *c0, *c1, *p1, p)
+            }
+            Segment::Arc(arc) => {
+                if arc.sweep.abs() < constants::EPS {
+                    return 0.0;
+                }
+                // angle from the start to p, measured in the direction of the arc
+                let alpha = (p - arc.center).angle();
+                let d =
+                    ((alpha - arc.start_angle) * arc.sweep.signum()).rem_euclid(2.0 * consts::PI);
+                let t = d / arc.sweep.abs();
+                if t <= 1.0 {
+                    t
+                } else if p.distance(self.start()) < p.distance(self.end()) {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+
+    // splits the segment at parameter t into two segments of the same type
+    pub fn split(&self, t: f64) -> (Segment, Segment) {
+        match self {
+            Segment::Line([p0, p1]) => {
+                let m = p0.lerp(*p1, t);
+                (Segment::Line([*p0, m]), Segment::Line([m, *p1]))
+            }
+            Segment::Cubic([p0, c0, c1, p1]) => {
+                let (l, r) = calc::split_cubic_bezier(*p0, *c0, *c1, *p1, t);
+                (Segment::Cubic(l), Segment::Cubic(r))
+            }
+            Segment::Arc(arc) => (
+                Segment::Arc(ArcData {
+                    sweep: t * arc.sweep,
+                    ..*arc
+                }),
+                Segment::Arc(ArcData {
+                    start_angle: arc.start_angle + t * arc.sweep,
+                    sweep: (1.0 - t) * arc.sweep,
+                    ..*arc
+                }),
+            ),
+        }
+    }
+
+    // the part of the segment between parameters t0 < t1
+    pub fn between(&self, t0: f64, t1: f64) -> Segment {
+        let (l, _) = self.split(t1);
+        if t1 < constants::EPS {
+            return l;
+        }
+        l.split(t0 / t1).1
+    }
+
+    // is the segment at most `tolerance` away from its chord
+    pub fn is_flat(&self, tolerance: f64) -> bool {
+        match self {
+            Segment::Line(_) => true,
+            Segment::Cubic([p0, c0, c1, p1]) => {
+                calc::distance_to_segment(*c0, *p0, *p1) <= tolerance
+                    && calc::distance_to_segment(*c1, *p0, *p1) <= tolerance
+            }
+            Segment::Arc(arc) => {
+                arc.sweep.abs() <= consts::PI
+                    && arc.radius * (1.0 - (arc.sweep / 2.0).cos()) <= tolerance
+            }
+        }
+    }
+
+    // points of a polyline that is at most `tolerance` away from the segment
+    pub fn points(&self, tolerance: f64) -> Vec<Pos2> {
+        match self {
+            Segment::Line([p0, p1]) => vec![*p0, *p1],
+            Segment::Cubic([p0, c0, c1, p1]) => {
+                calc::cubic_bezier_points(*p0, *p1, *c0, *c1, tolerance)
+            }
+            Segment::Arc(arc) => calc::arc_points(*arc, tolerance),
+        }
+    }
+
+    pub fn length(&self) -> f64 {
+        match self {
+            Segment::Line([p0, p1]) => p0.distance(*p1),
+            Segment::Cubic([p0, c0, c1, p1]) => {
+                let n = constants::ARC_LENGTH_SUBDIVISIONS;
+                (0..n)
+                    .map(|k| {
+                        calc::integrate(
+                            |t| {
+                                calc::cubic_bezier_derivatives(*p0, *c0, *c1, *p1, t)
+                                    .0
+                                    .length()
+                            },
+                            k as f64 / n as f64,
+                            (k + 1) as f64 / n as f64,
+                        )
+                    })
+                    .sum()
+            }
+            Segment::Arc(arc) => arc.radius * arc.sweep.abs(),
+        }
+    }
+
+    // contribution of the segment to the signed area of a closed outline,
+    // from Green's theorem: 1/2 * integral of (x dy - y dx)
+    pub fn area(&self) -> f64 {
+        match self {
+            Segment::Line([p0, p1]) => calc::cross(p0.to_vec2(), p1.to_vec2()) / 2.0,
+            Segment::Cubic(p) => {
+                let c = |i: usize, j: usize| calc::cross(p[i].to_vec2(), p[j].to_vec2());
+                (6.0 * c(0, 1)
+                    + 3.0 * c(0, 2)
+                    + c(0, 3)
+                    + 3.0 * c(1, 2)
+                    + 3.0 * c(1, 3)
+                    + 6.0 * c(2, 3))
+                    / 20.0
+            }
+            Segment::Arc(arc) => {
+                let (alpha0, alpha1) = (arc.start_angle, arc.start_angle + arc.sweep);
+                let du = Vec2::angled(alpha1) - Vec2::angled(alpha0);
+                (arc.radius * calc::cross(arc.center.to_vec2(), du)
+                    + arc.radius * arc.radius * arc.sweep)
+                    / 2.0
+            }
+        }
+    }
+
+    // contributions of the segment to the first moments of area of a closed outline:
+    // (1/2 * integral of x^2 dy, -1/2 * integral of y^2 dx)
+    pub fn moments(&self) -> Vec2 {
+        match self {
+            Segment::Line([p0, p1]) => Vec2::new(
+                (p1.y - p0.y) * (p0.x * p0.x + p0.x * p1.x + p1.x * p1.x) / 6.0,
+                -(p1.x - p0.x) * (p0.y * p0.y + p0.y * p1.y + p1.y * p1.y) / 6.0,
+            ),
+            Segment::Cubic([p0, c0, c1, p1]) => {
+                // the integrands are polynomials of degree 8, so the quadrature is exact
+                let f = |t: f64| {
+                    let p = calc::cubic_bezier_point(*p0, *c0, *c1, *p1, t);
+                    let d = calc::cubic_bezier_derivatives(*p0, *c0, *c1, *p1, t).0;
+                    Vec2::new(p.x * p.x * d.y, -p.y * p.y * d.x) / 2.0
+                };
+                Vec2::new(
+                    calc::integrate(|t| f(t).x, 0.0, 1.0),
+                    calc::integrate(|t| f(t).y, 0.0, 1.0),
+                )
+            }
+            Segment::Arc(arc) => {
+                let (s, r) = (arc.center, arc.radius);
+                // antiderivatives of the integrands in terms of the angle
+                let mx = |a: f64| {
+                    let sin = a.sin();
+                    s.x * s.x * r * sin
+                        + 2.0 * s.x * r * r * (a / 2.0 + (2.0 * a).sin() / 4.0)
+                        + r * r * r * (sin - sin * sin * sin / 3.0)
+                };
+                let my = |a: f64| {
+                    let cos = a.cos();
+                    -s.y * s.y * r * cos
+                        + 2.0 * s.y * r * r * (a / 2.0 - (2.0 * a).sin() / 4.0)
+                        + r * r * r * (-cos + cos * cos * cos / 3.0)
+                };
+                let (alpha0, alpha1) = (arc.start_angle, arc.start_angle + arc.sweep);
+                Vec2::new(mx(alpha1) - mx(alpha0), my(alpha1) - my(alpha0)) / 2.0
+            }
+        }
+    }
+
+    // the smallest axis-aligned rectangle containing the segment
+    pub fn bounding_box(&self) -> Rect {
+        let mut points = vec![self.start(), self.end()];
+        match self {
+            Segment::Line(_) => (),
+            Segment::Cubic([p0, c0, c1, p1]) => {
+                // extrema are where a coordinate of the derivative vanishes
+                let (a, b, c) = (*c0 - *p0, *c1 - *c0, *p1 - *c1);
+                let roots_x = calc::quadratic_roots(a.x - 2.0 * b.x + c.x, 2.0 * (b.x - a.x), a.x);
+                let roots_y = calc::quadratic_roots(a.y - 2.0 * b.y + c.y, 2.0 * (b.y - a.y), a.y);
+                points.extend(
+                    roots_x
+                        .into_iter()
+                        .chain(roots_y)
+                        .filter(|t| (0.0..=1.0).contains(t))
+                        .map(|t| self.point_at(t)),
+                );
+            }
+            Segment::Arc(arc) => {
+                // extrema are at multiples of 90 degrees
+                let (lo, hi) = if arc.sweep >= 0.0 {
+                    (arc.start_angle, arc.start_angle + arc.sweep)
+                } else {
+                    (arc.start_angle + arc.sweep, arc.start_angle)
+                };
+                let first = (lo / consts::FRAC_PI_2).ceil() as i32;
+                let last = (hi / consts::FRAC_PI_2).floor() as i32;
+                points.extend((first..=last).map(|k| arc.point_at(k as f64 * consts::FRAC_PI_2)));
+            }
+        }
+
+        Rect::from_points(&points)
+    }
+}
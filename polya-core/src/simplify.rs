@@ -0,0 +1,226 @@
+use crate::{
+    calc,
+    math::Pos2,
+    polygon::Polygon,
+    vertex::{Vertex, VertexConstraint},
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SimplifyAlgorithm {
+    // keeps the vertices farthest from the chord of each run
+    #[default]
+    DouglasPeucker,
+    // drops the vertices making the smallest triangles with their neighbors
+    Visvalingam,
+}
+
+fn is_plain_line(v: &Vertex) -> bool {
+    v.bezier.is_none() && v.arc.is_none() && v.edge_c.is_none()
+}
+
+// whether the joint at v_i continues a curve tangentially,
+// which makes it depend on the direction of its straight neighbor edge
+fn is_smooth_curve_joint(polygon: &Polygon, v_i: usize) -> bool {
+    let n = polygon.vertices.len();
+    let (prev, v) = (&polygon.vertices[(v_i + n - 1) % n], &polygon.vertices[v_i]);
+    let curved = |v: &Vertex| v.bezier.is_some() || v.arc.is_some();
+
+    !matches!(v.vertex_c, VertexConstraint::G0) && (curved(prev) || curved(v))
+}
+
+// a vertex can go if both of its edges are unconstrained straight lines
+// and neither neighbor relies on the direction of the edge it shares with it
+fn is_removable(polygon: &Polygon, v_i: usize) -> bool {
+    let n = polygon.vertices.len();
+    let (prev_i, next_i) = ((v_i + n - 1) % n, (v_i + 1) % n);
+
+    is_plain_line(&polygon.vertices[prev_i])
+        && is_plain_line(&polygon.vertices[v_i])
+        && !is_smooth_curve_joint(polygon, prev_i)
+        && !is_smooth_curve_joint(polygon, next_i)
+}
+
+// marks the points of the run to keep, its ends are always kept
+fn douglas_peucker(points: &[Pos2], tolerance: f64, keep: &mut [bool]) {
+    let last = points.len() - 1;
+    keep[0] = true;
+    keep[last] = true;
+    let farthest = (1..last)
+        .map(|i| {
+            (
+                i,
+                calc::distance_to_segment(points[i], points[0], points[last]),
+            )
+        })
+        .max_by(|(_, d0), (_, d1)| d0.total_cmp(d1));
+    if let Some((i, dist)) = farthest
+        && dist > tolerance
+    {
+        douglas_peucker(&points[..=i], tolerance, &mut keep[..=i]);
+        douglas_peucker(&points[i..], tolerance, &mut keep[i..]);
+    }
+}
+
+// marks the points of the run to keep, its ends are always kept;
+// only points within the tolerance from the line through their neighbors can go
+fn visvalingam(points: &[Pos2], tolerance: f64, keep: &mut [bool]) {
+    keep.fill(true);
+    let mut left: Vec<usize> = (0..points.len()).collect();
+    let cost = |left: &[usize], k: usize| {
+        let (a, b, c) = (points[left[k - 1]], points[left[k]], points[left[k + 1]]);
+        if calc::distance_to_segment(b, a, c) > tolerance {
+            f64::INFINITY
+        } else {
+            calc::cross(b - a, c - b).abs() / 2.0
+        }
+    };
+    // the ends always stay
+    let mut areas: Vec<f64> = (0..left.len())
+        .map(|k| {
+            if k == 0 || k == left.len() - 1 {
+                f64::INFINITY
+            } else {
+                cost(&left, k)
+            }
+        })
+        .collect();
+    while let Some((k, &area)) = areas
+        .iter()
+        .enumerate()
+        .min_by(|(_, a0), (_, a1)| a0.total_cmp(a1))
+        && area.is_finite()
+    {
+        keep[left[k]] = false;
+        left.remove(k);
+        areas.remove(k);
+        // the areas of the neighbors never drop below the one just removed,
+        // so that points are removed in order of significance
+        for j in [k - 1, k] {
+            if j > 0 && j < left.len() - 1 {
+                areas[j] = cost(&left, j).max(area);
+            }
+        }
+    }
+}
+
+// indices of the vertices left after removing the ones that don't change
+// the straight parts of the outline by more than the tolerance;
+// vertices of curves and constrained edges are never removed
+pub fn simplify(polygon: &Polygon, algo: SimplifyAlgorithm, tolerance: f64) -> Vec<usize> {
+    let n = polygon.vertices.len();
+    let mut fixed: Vec<bool> = (0..n).map(|v_i| !is_removable(polygon, v_i)).collect();
+    // with nothing to hold on to, the run starts and ends at the vertices farthest apart
+    if !fixed.contains(&true) {
+        let p0 = polygon.vertices[0].p;
+        let far_i = (0..n)
+            .max_by(|&a, &b| {
+                p0.distance(polygon.vertices[a].p)
+                    .total_cmp(&p0.distance(polygon.vertices[b].p))
+            })
+            .unwrap_or(0);
+        fixed[0] = true;
+        fixed[far_i] = true;
+    }
+
+    let mut keep = fixed.clone();
+    for start_i in (0..n).filter(|&v_i| fixed[v_i]) {
+        // the run goes from one fixed vertex to the next one
+        let mut run = vec![start_i];
+        let mut v_i = (start_i + 1) % n;
+        loop {
+            run.push(v_i);
+            if fixed[v_i] {
+                break;
+            }
+            v_i = (v_i + 1) % n;
+        }
+        if run.len() <= 2 {
+            continue;
+        }
+        let points: Vec<Pos2> = run.iter().map(|&v_i| polygon.vertices[v_i].p).collect();
+        let mut run_keep = vec![false; run.len()];
+        match algo {
+            SimplifyAlgorithm::DouglasPeucker => douglas_peucker(&points, tolerance, &mut run_keep),
+            SimplifyAlgorithm::Visvalingam => visvalingam(&points, tolerance, &mut run_keep),
+        }
+        for (&v_i, &k) in run.iter().zip(run_keep.iter()) {
+            keep[v_i] |= k;
+        }
+    }
+
+    let kept: Vec<usize> = (0..n).filter(|&v_i| keep[v_i]).collect();
+    if kept.len() < 3 {
+        return (0..n).collect();
+    }
+
+    kept
+}
+
+// the polygon made of the kept vertices only
+pub fn simplified(polygon: &Polygon, kept: &[usize]) -> Polygon {
+    Polygon {
+        hole: polygon.hole,
+        reject_self_intersections: polygon.reject_self_intersections,
+        ..Polygon::new(kept.iter().map(|&v_i| polygon.vertices[v_i]).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{math::Vec2, vertex::EdgeConstraint};
+
+    // a square whose sides wiggle by at most 0.5 around the straight line
+    fn noisy_square() -> Polygon {
+        let mut points = Vec::new();
+        for (corner, dir) in [
+            (Pos2::new(0.0, 0.0), Vec2::new(1.0, 0.0)),
+            (Pos2::new(100.0, 0.0), Vec2::new(0.0, 1.0)),
+            (Pos2::new(100.0, 100.0), Vec2::new(-1.0, 0.0)),
+            (Pos2::new(0.0, 100.0), Vec2::new(0.0, -1.0)),
+        ] {
+            for k in 0..10 {
+                let wiggle = if k == 0 { 0.0 } else { 0.5 * (k as f64).sin() };
+                points.push(corner + 10.0 * k as f64 * dir + wiggle * dir.rot90());
+            }
+        }
+        Polygon::from_points(&points, false)
+    }
+
+    fn area(polygon: &Polygon) -> f64 {
+        polygon
+            .segments()
+            .map(|segment| segment.area())
+            .sum::<f64>()
+            .abs()
+    }
+
+    #[test]
+    fn simplify_keeps_the_corners() {
+        let polygon = noisy_square();
+        for algo in [
+            SimplifyAlgorithm::DouglasPeucker,
+            SimplifyAlgorithm::Visvalingam,
+        ] {
+            let kept = simplify(&polygon, algo, 1.0);
+            assert_eq!(kept, vec![0, 10, 20, 30], "{algo:?}");
+            let result = simplified(&polygon, &kept);
+            assert!((area(&result) - 10000.0).abs() < 1e-6, "{algo:?}");
+            // a tolerance below the wiggles keeps the outline as it is
+            assert_eq!(simplify(&polygon, algo, 0.01).len(), 40, "{algo:?}");
+        }
+    }
+
+    #[test]
+    fn simplify_keeps_constrained_edges() {
+        let mut polygon = noisy_square();
+        polygon.vertices[4].edge_c = Some(EdgeConstraint::FixedLength(10.0));
+        for algo in [
+            SimplifyAlgorithm::DouglasPeucker,
+            SimplifyAlgorithm::Visvalingam,
+        ] {
+            let kept = simplify(&polygon, algo, 1.0);
+            assert!(kept.contains(&4) && kept.contains(&5), "{algo:?}: {kept:?}");
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::f64::consts;
+
+use crate::{
+    calc, constants,
+    math::{Pos2, Rect, Vec2},
+};
+
+// an affine map keeping the pivot in place:
+// p -> pivot + (p - pivot).x * x_axis + (p - pivot).y * y_axis
+#[derive(Clone, Copy, Debug)]
+pub struct Transform {
+    pub pivot: Pos2,
+    pub x_axis: Vec2,
+    pub y_axis: Vec2,
+}
+
+impl Transform {
+    // a positive angle turns clockwise on the screen (the y axis points down)
+    pub fn rotation(pivot: Pos2, angle: f64) -> Self {
+        Self {
+            pivot,
+            x_axis: Vec2::angled(angle),
+            y_axis: Vec2::angled(angle + consts::FRAC_PI_2),
+        }
+    }
+
+    // a negative factor mirrors across the axis going through the pivot
+    pub fn scale(pivot: Pos2, sx: f64, sy: f64) -> Self {
+        Self {
+            pivot,
+            x_axis: Vec2::new(sx, 0.0),
+            y_axis: Vec2::new(0.0, sy),
+        }
+    }
+
+    pub fn apply(&self, p: Pos2) -> Pos2 {
+        let d = p - self.pivot;
+        self.pivot + d.x * self.x_axis + d.y * self.y_axis
+    }
+
+    // the factor by which areas change, negative if the map mirrors
+    pub fn det(&self) -> f64 {
+        calc::cross(self.x_axis, self.y_axis)
+    }
+
+    // whether the map keeps the shape of figures (circles stay circles),
+    // i.e. the axes are perpendicular and equally long
+    pub fn is_uniform(&self) -> bool {
+        let (x, y) = (self.x_axis, self.y_axis);
+        let scale = x.length().max(y.length());
+        (x.length() - y.length()).abs() <= constants::DOT_EPS * scale
+            && x.dot(y).abs() <= constants::DOT_EPS * scale * scale
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Handle {
+    Pivot,
+    Rotate,
+    // uniform scaling from the corners of the bounding box
+    Scale,
+    // scaling along one axis from the middles of the sides
+    ScaleX,
+    ScaleY,
+}
+
+// handles around the bounding box of the transformed shape
+pub fn handles(bbox: Rect, pivot: Pos2) -> Vec<(Handle, Pos2)> {
+    vec![
+        (Handle::Pivot, pivot),
+        (
+            Handle::Rotate,
+            bbox.center_top() - Vec2::new(0.0, constants::SIZE_ROTATE_HANDLE_OFFSET),
+        ),
+        (Handle::Scale, bbox.left_top()),
+        (Handle::Scale, bbox.right_top()),
+        (Handle::Scale, bbox.left_bottom()),
+        (Handle::Scale, bbox.right_bottom()),
+        (Handle::ScaleX, bbox.left_center()),
+        (Handle::ScaleX, bbox.right_center()),
+        (Handle::ScaleY, bbox.center_top()),
+        (Handle::ScaleY, bbox.center_bottom()),
+    ]
+}
+
+pub fn nearby_handle(bbox: Rect, pivot: Pos2, p: Pos2) -> Option<Handle> {
+    handles(bbox, pivot)
+        .into_iter()
+        .find(|(_, q)| q.distance(p) <= constants::SIZE_CONTROL_VERTEX)
+        .map(|(handle, _)| handle)
+}
+
+// ratio of the distances from the pivot along one direction,
+// None if the handle started too close to the pivot to tell
+fn ratio(from: f64, to: f64) -> Option<f64> {
+    (from.abs() >= constants::SIZE_VERTEX)
+        .then(|| to / from)
+        .filter(|s| s.abs() >= constants::MIN_SCALE)
+}
+
+// the transform taking the handle grabbed at `from` to `to`,
+// rotations snap to multiples of ROTATION_SNAP if `snap` is set
+pub fn from_drag(
+    handle: Handle,
+    pivot: Pos2,
+    from: Pos2,
+    to: Pos2,
+    snap: bool,
+) -> Option<Transform> {
+    let (u, w) = (from - pivot, to - pivot);
+    match handle {
+        Handle::Pivot => None,
+        Handle::Rotate => {
+            let mut angle = calc::cross(u, w).atan2(u.dot(w));
+            if snap {
+                let step = constants::ROTATION_SNAP.to_radians();
+                angle = (angle / step).round() * step;
+            }
+            Some(Transform::rotation(pivot, angle))
+        }
+        Handle::Scale => {
+            let s = ratio(u.length(), w.dot(u) / u.length().max(constants::EPS))?;
+            Some(Transform::scale(pivot, s, s))
+        }
+        Handle::ScaleX => Some(Transform::scale(pivot, ratio(u.x, w.x)?, 1.0)),
+        Handle::ScaleY => Some(Transform::scale(pivot, 1.0, ratio(u.y, w.y)?)),
+    }
+}
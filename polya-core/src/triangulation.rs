@@ -0,0 +1,399 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{boolean, calc, constants, math::Pos2, units::Units};
+
+// triangles given by indices into the vertex list,
+// all going around the same way as an outline with positive signed area
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    pub vertices: Vec<Pos2>,
+    pub triangles: Vec<[usize; 3]>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TriangulationError {
+    SelfIntersecting,
+    Degenerate,
+}
+
+impl fmt::Display for TriangulationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TriangulationError::SelfIntersecting => write!(f, "the outline intersects itself"),
+            TriangulationError::Degenerate => write!(f, "the outline has no area"),
+        }
+    }
+}
+
+impl Mesh {
+    // Wavefront OBJ, which most mesh tools can read (indices start at 1),
+    // OBJ has no notion of units so the coordinates are converted to the document's
+    pub fn to_obj(&self, units: Units) -> String {
+        let mut obj = format!("# triangulated by polya, in {}\n", units.unit);
+        for p in self.vertices.iter() {
+            obj += &format!("v {} {} 0\n", units.to_unit(p.x), units.to_unit(p.y));
+        }
+        for [a, b, c] in self.triangles.iter() {
+            obj += &format!("f {} {} {}\n", a + 1, b + 1, c + 1);
+        }
+
+        obj
+    }
+}
+
+fn orientation(a: Pos2, b: Pos2, c: Pos2) -> f64 {
+    calc::cross(b - a, c - b)
+}
+
+// do any two edges of the contours cross or touch (apart from neighbors sharing a vertex)
+fn has_intersections(contours: &[Vec<Pos2>]) -> bool {
+    let edges: Vec<_> = contours
+        .iter()
+        .enumerate()
+        .flat_map(|(c_i, contour)| {
+            (0..contour.len()).map(move |i| (c_i, i, contour[i], contour[(i + 1) % contour.len()]))
+        })
+        .collect();
+    edges.iter().enumerate().any(|(k, &(c_i, i, a, b))| {
+        edges[(k + 1)..].iter().any(|&(c_j, j, c, d)| {
+            let n = contours[c_i].len();
+            let neighbors = c_i == c_j && (j == (i + 1) % n || i == (j + 1) % n);
+            !neighbors && calc::segments_intersection(a, b, c, d).is_some()
+        })
+    })
+}
+
+// is p inside or on the boundary of the triangle abc with positive orientation
+fn in_triangle(p: Pos2, a: Pos2, b: Pos2, c: Pos2) -> bool {
+    orientation(a, b, p) >= 0.0 && orientation(b, c, p) >= 0.0 && orientation(c, a, p) >= 0.0
+}
+
+// can the ring vertex k be joined to m, which is a vertex of one of the holes: the bridge
+// has to leave k into the inside of the ring and can't cross the ring or any hole elsewhere;
+// the ring is checked as a whole, holes bridged before included, since after that
+// some of its vertices appear twice and their neighbors say little about what's visible
+fn is_visible(points: &[Pos2], ring: &[usize], holes: &[Vec<usize>], m: Pos2, k: usize) -> bool {
+    let n = ring.len();
+    let p = points[ring[k]];
+    let (prev, next) = (points[ring[(k + n - 1) % n]], points[ring[(k + 1) % n]]);
+    let (to_prev, to_next, to_m) = (prev - p, next - p, m - p);
+    let inside = if orientation(prev, p, next) >= 0.0 {
+        calc::cross(to_next, to_m) > 0.0 && calc::cross(to_m, to_prev) > 0.0
+    } else {
+        calc::cross(to_next, to_m) > 0.0 || calc::cross(to_m, to_prev) > 0.0
+    };
+    let touches = |q: Pos2| q.distance(p) < constants::EPS || q.distance(m) < constants::EPS;
+    let ring_edges = (0..n).map(|j| (points[ring[j]], points[ring[(j + 1) % n]]));
+    let hole_edges = holes.iter().flat_map(|hole| {
+        (0..hole.len()).map(|j| (points[hole[j]], points[hole[(j + 1) % hole.len()]]))
+    });
+
+    inside
+        && ring_edges.chain(hole_edges).all(|(a, b)| {
+            touches(a) || touches(b) || calc::segments_intersection(m, p, a, b).is_none()
+        })
+}
+
+// joins a hole to the outer ring with a pair of edges going both ways,
+// from the hole's rightmost vertex to a vertex of the ring visible from it
+// (David Eberly, "Triangulation by Ear Clipping")
+fn bridge_hole(points: &[Pos2], ring: &mut Vec<usize>, hole: &[usize], holes: &[Vec<usize>]) {
+    let m_i = (0..hole.len())
+        .max_by(|&a, &b| points[hole[a]].x.total_cmp(&points[hole[b]].x))
+        .unwrap_or(0);
+    let m = points[hole[m_i]];
+
+    // the nearest edge hit by the ray going right from m
+    let mut best: Option<(f64, usize)> = None;
+    for k in 0..ring.len() {
+        let (a, b) = (points[ring[k]], points[ring[(k + 1) % ring.len()]]);
+        if (a.y > m.y) == (b.y > m.y) {
+            continue;
+        }
+        let x = a.x + (m.y - a.y) / (b.y - a.y) * (b.x - a.x);
+        if x >= m.x && best.is_none_or(|(best_x, _)| x < best_x) {
+            best = Some((x, k));
+        }
+    }
+    let Some((x, k)) = best else {
+        return;
+    };
+    let hit = Pos2::new(x, m.y);
+    let (a_k, b_k) = (k, (k + 1) % ring.len());
+    let mut p_k = if points[ring[a_k]].x > points[ring[b_k]].x {
+        a_k
+    } else {
+        b_k
+    };
+    // reflex vertices inside the triangle m-hit-p could block the view of p,
+    // of those the one making the smallest angle with the ray is visible
+    let p = points[ring[p_k]];
+    let (t0, t1) = if orientation(m, hit, p) >= 0.0 {
+        (hit, p)
+    } else {
+        (p, hit)
+    };
+    let n = ring.len();
+    let blocking = (0..n)
+        .filter(|&r_k| {
+            let r = points[ring[r_k]];
+            r_k != p_k
+                && orientation(
+                    points[ring[(r_k + n - 1) % n]],
+                    r,
+                    points[ring[(r_k + 1) % n]],
+                ) < 0.0
+                && in_triangle(r, m, t0, t1)
+        })
+        .min_by(|&r0, &r1| {
+            let angle = |r_k: usize| {
+                let d = points[ring[r_k]] - m;
+                (d.y.abs().atan2(d.x), d.length())
+            };
+            angle(r0)
+                .partial_cmp(&angle(r1))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    if let Some(r_k) = blocking {
+        p_k = r_k;
+    }
+    // the search above only sees the vertices of a simple ring, so the vertex it finds
+    // can be hidden behind another hole, then the nearest visible one is taken
+    if !is_visible(points, ring, holes, m, p_k) {
+        let Some(k) = (0..n)
+            .filter(|&k| is_visible(points, ring, holes, m, k))
+            .min_by(|&k0, &k1| {
+                m.distance(points[ring[k0]])
+                    .total_cmp(&m.distance(points[ring[k1]]))
+            })
+        else {
+            return;
+        };
+        p_k = k;
+    }
+
+    let mut bridged = ring[..=p_k].to_vec();
+    bridged.extend((0..=hole.len()).map(|j| hole[(m_i + j) % hole.len()]));
+    bridged.extend_from_slice(&ring[p_k..]);
+    *ring = bridged;
+}
+
+// repeatedly cuts off ears: triangles made by a convex vertex and its neighbors
+// that don't contain any other vertex of the ring
+fn clip_ears(points: &[Pos2], mut ring: Vec<usize>) -> Vec<[usize; 3]> {
+    let mut triangles = Vec::with_capacity(ring.len());
+    while ring.len() >= 3 {
+        let n = ring.len();
+        let corners = |k: usize| (ring[(k + n - 1) % n], ring[k], ring[(k + 1) % n]);
+        let is_ear = |k: usize| {
+            let (a, b, c) = corners(k);
+            let (pa, pb, pc) = (points[a], points[b], points[c]);
+            orientation(pa, pb, pc) > 0.0
+                && (0..n).all(|j| {
+                    let (prev, v, next) = corners(j);
+                    let pv = points[v];
+                    [a, b, c].contains(&v)
+                        || [pa, pb, pc].iter().any(|q| q.distance(pv) < constants::EPS)
+                        || orientation(points[prev], pv, points[next]) > 0.0
+                        || !in_triangle(pv, pa, pb, pc)
+                })
+        };
+        // with rounding errors there may be no proper ear left,
+        // then any convex vertex will do
+        let Some(k) = (0..n).find(|&k| is_ear(k)).or_else(|| {
+            (0..n).find(|&k| {
+                let (a, b, c) = corners(k);
+                orientation(points[a], points[b], points[c]) > 0.0
+            })
+        }) else {
+            break;
+        };
+        let (a, b, c) = corners(k);
+        triangles.push([a, b, c]);
+        ring.remove(k);
+    }
+
+    triangles
+}
+
+// is d inside the circumcircle of the triangle abc with positive orientation
+fn in_circumcircle(a: Pos2, b: Pos2, c: Pos2, d: Pos2) -> bool {
+    let rel = |p: Pos2| (p.x - d.x, p.y - d.y);
+    let ((ax, ay), (bx, by), (cx, cy)) = (rel(a), rel(b), rel(c));
+    let det = (ax * ax + ay * ay) * (bx * cy - cx * by) - (bx * bx + by * by) * (ax * cy - cx * ay)
+        + (cx * cx + cy * cy) * (ax * by - bx * ay);
+
+    det > 0.0
+}
+
+// flips edges shared by two triangles until no triangle's circumcircle contains
+// the opposite vertex of its neighbor (Lawson's algorithm), edges of the outline
+// belong to a single triangle so they're never flipped
+fn make_delaunay(points: &[Pos2], triangles: &mut [[usize; 3]]) {
+    for _ in 0..constants::MAX_DELAUNAY_PASSES {
+        let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+        for (t_i, t) in triangles.iter().enumerate() {
+            for k in 0..3 {
+                edges.insert((t[k], t[(k + 1) % 3]), t_i);
+            }
+        }
+        let mut flipped = vec![false; triangles.len()];
+        let mut any = false;
+        for t_i in 0..triangles.len() {
+            for k in 0..3 {
+                if flipped[t_i] {
+                    break;
+                }
+                let [a, b, c] = [0, 1, 2].map(|j| triangles[t_i][(k + j) % 3]);
+                let Some(&u_i) = edges.get(&(b, a)) else {
+                    continue;
+                };
+                if flipped[u_i] || u_i == t_i {
+                    continue;
+                }
+                let Some(&d) = triangles[u_i].iter().find(|&&v| v != a && v != b) else {
+                    continue;
+                };
+                let (pa, pb, pc, pd) = (points[a], points[b], points[c], points[d]);
+                if in_circumcircle(pa, pb, pc, pd)
+                    && orientation(pa, pd, pc) > 0.0
+                    && orientation(pd, pb, pc) > 0.0
+                {
+                    triangles[t_i] = [a, d, c];
+                    triangles[u_i] = [d, b, c];
+                    flipped[t_i] = true;
+                    flipped[u_i] = true;
+                    any = true;
+                }
+            }
+        }
+        if !any {
+            break;
+        }
+    }
+}
+
+// triangulates the region bounded by the outer contour and the holes inside it,
+// optionally making it a constrained Delaunay triangulation
+pub fn triangulate(
+    outer: &[Pos2],
+    holes: &[Vec<Pos2>],
+    delaunay: bool,
+) -> Result<Mesh, TriangulationError> {
+    let contours: Vec<Vec<Pos2>> = std::iter::once(outer.to_vec())
+        .chain(holes.iter().cloned())
+        .filter(|contour| contour.len() >= 3)
+        .collect();
+    if has_intersections(&contours) {
+        return Err(TriangulationError::SelfIntersecting);
+    }
+    if contours.is_empty() || boolean::signed_area(&contours[0]).abs() < constants::EPS {
+        return Err(TriangulationError::Degenerate);
+    }
+
+    let points: Vec<Pos2> = contours.iter().flatten().copied().collect();
+    // outer ring with positive signed area, holes with negative
+    let mut start = 0;
+    let mut rings: Vec<Vec<usize>> = contours
+        .iter()
+        .enumerate()
+        .map(|(c_i, contour)| {
+            let mut ring: Vec<usize> = (start..start + contour.len()).collect();
+            start += contour.len();
+            if (boolean::signed_area(contour) > 0.0) != (c_i == 0) {
+                ring.reverse();
+            }
+            ring
+        })
+        .collect();
+    let mut ring = rings.remove(0);
+    // holes further right are joined first, so that most bridges go straight to the outline
+    let max_x = |hole: &Vec<usize>| {
+        hole.iter()
+            .map(|&i| points[i].x)
+            .fold(f64::NEG_INFINITY, f64::max)
+    };
+    rings.sort_by(|a, b| max_x(b).total_cmp(&max_x(a)));
+    for hole in rings.iter() {
+        bridge_hole(&points, &mut ring, hole, &rings);
+    }
+
+    let mut triangles = clip_ears(&points, ring);
+    if delaunay {
+        make_delaunay(&points, &mut triangles);
+    }
+
+    Ok(Mesh {
+        vertices: points,
+        triangles,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f64, y: f64, size: f64) -> Vec<Pos2> {
+        vec![
+            Pos2::new(x, y),
+            Pos2::new(x + size, y),
+            Pos2::new(x + size, y + size),
+            Pos2::new(x, y + size),
+        ]
+    }
+
+    fn mesh_area(mesh: &Mesh) -> f64 {
+        mesh.triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                let (a, b, c) = (mesh.vertices[a], mesh.vertices[b], mesh.vertices[c]);
+                calc::cross(b - a, c - a) / 2.0
+            })
+            .sum()
+    }
+
+    // the triangles cover the region exactly once if their areas add up to it
+    fn assert_covers(outer: &[Pos2], holes: &[Vec<Pos2>]) {
+        let expected = boolean::signed_area(outer).abs()
+            - holes
+                .iter()
+                .map(|hole| boolean::signed_area(hole).abs())
+                .sum::<f64>();
+        for delaunay in [false, true] {
+            let mesh = triangulate(outer, holes, delaunay).unwrap();
+            assert!(
+                (mesh_area(&mesh) - expected).abs() < 1e-6,
+                "{} != {expected} with holes {holes:?}",
+                mesh_area(&mesh)
+            );
+        }
+    }
+
+    #[test]
+    fn triangulate_covers_a_region_with_one_hole() {
+        assert_covers(&square(0.0, 0.0, 100.0), &[square(30.0, 30.0, 40.0)]);
+    }
+
+    #[test]
+    fn triangulate_covers_a_region_with_many_holes() {
+        let outer = square(0.0, 0.0, 100.0);
+        for second in [
+            square(60.0, 60.0, 20.0),
+            square(55.0, 60.0, 20.0),
+            square(61.0, 60.0, 19.0),
+        ] {
+            assert_covers(&outer, &[square(60.0, 10.0, 20.0), second]);
+        }
+        assert_covers(
+            &outer,
+            &[
+                square(10.0, 10.0, 20.0),
+                square(40.0, 10.0, 20.0),
+                square(70.0, 10.0, 20.0),
+                square(10.0, 70.0, 20.0),
+                square(45.0, 45.0, 10.0),
+                square(70.0, 70.0, 20.0),
+            ],
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Unit {
+    Mm,
+    Cm,
+    In,
+    #[default]
+    Px,
+}
+
+impl Unit {
+    pub const ALL: [Unit; 4] = [Unit::Mm, Unit::Cm, Unit::In, Unit::Px];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Unit::Mm => "mm",
+            Unit::Cm => "cm",
+            Unit::In => "in",
+            Unit::Px => "px",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|unit| unit.name() == name)
+    }
+
+    // model units per unit when the model is drawn at 96 points per inch, like in CSS
+    pub fn default_scale(self) -> f64 {
+        match self {
+            Unit::Mm => 96.0 / 25.4,
+            Unit::Cm => 96.0 / 2.54,
+            Unit::In => 96.0,
+            Unit::Px => 1.0,
+        }
+    }
+
+    // decimal places that are worth showing, about a hundredth of a millimeter
+    pub fn precision(self) -> usize {
+        match self {
+            Unit::Mm => 2,
+            Unit::Cm => 3,
+            Unit::In => 4,
+            Unit::Px => 1,
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// the real-world unit of a document, the model itself has one unit per point on the screen
+// and `scale` of them make up one unit of the document
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Units {
+    pub unit: Unit,
+    pub scale: f64,
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        Self::new(Unit::default())
+    }
+}
+
+impl Units {
+    pub fn new(unit: Unit) -> Self {
+        Self {
+            unit,
+            scale: unit.default_scale(),
+        }
+    }
+
+    pub fn to_unit(self, len: f64) -> f64 {
+        len / self.scale
+    }
+
+    pub fn from_unit(self, len: f64) -> f64 {
+        len * self.scale
+    }
+
+    pub fn area_to_unit(self, area: f64) -> f64 {
+        area / (self.scale * self.scale)
+    }
+
+    // a length of the model in the document's unit, with the unit
+    pub fn format(self, len: f64) -> String {
+        format!(
+            "{:.*} {}",
+            self.unit.precision(),
+            self.to_unit(len),
+            self.unit
+        )
+    }
+
+    pub fn format_area(self, area: f64) -> String {
+        format!(
+            "{:.*} {}²",
+            self.unit.precision(),
+            self.area_to_unit(area),
+            self.unit
+        )
+    }
+}
@@ -1,38 +1,56 @@
-use egui::Pos2;
+use crate::{constants, math::Pos2};
 
-use crate::constants;
-
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum EdgeConstraint {
     Vertical,
+    Horizontal,
     DiagonalUp,   // /
     DiagonalDown, // \
-    FixedLength(f32),
+    FixedLength(f64),
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub enum VertexConstraint {
     G0,
     G1,
     #[default]
     C1,
+    // curvature continuity, only between two Bézier segments
+    G2,
+    C2,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct CubicBezier {
     pub control: [Pos2; 2],
 }
 
-#[derive(Clone, Copy, Debug)]
-pub struct CircleArc;
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CircleArc {
+    // None if the radius follows from the continuity at the endpoints
+    pub radius: Option<f64>,
+    // whether to take the longer of the two arcs with the given radius
+    pub large: bool,
+    pub clockwise: bool,
+}
+
+// which dimensions are drawn for the edge starting at a vertex,
+// the angle is the one between this edge and the previous one
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Dimensions {
+    pub length: bool,
+    pub angle: bool,
+    pub radius: bool,
+}
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Vertex {
     pub p: Pos2,
     pub bezier: Option<CubicBezier>,
     pub arc: Option<CircleArc>,
     pub edge_c: Option<EdgeConstraint>,
     pub vertex_c: VertexConstraint,
+    pub dims: Dimensions,
 }
 
 impl CubicBezier {
@@ -56,14 +74,35 @@ impl CubicBezier {
     }
 }
 
-impl From<(f32, f32)> for Vertex {
-    fn from(pair: (f32, f32)) -> Self {
+impl Default for CircleArc {
+    fn default() -> Self {
+        Self {
+            radius: None,
+            large: false,
+            clockwise: true,
+        }
+    }
+}
+
+impl CircleArc {
+    pub fn new(radius: Option<f64>, large: bool, clockwise: bool) -> Self {
+        Self {
+            radius,
+            large,
+            clockwise,
+        }
+    }
+}
+
+impl From<(f64, f64)> for Vertex {
+    fn from(pair: (f64, f64)) -> Self {
         Self {
             p: Pos2::from(pair),
             bezier: None,
             arc: None,
             edge_c: None,
             vertex_c: VertexConstraint::default(),
+            dims: Dimensions::default(),
         }
     }
 }
@@ -76,6 +115,7 @@ impl Vertex {
             arc: None,
             edge_c: None,
             vertex_c: VertexConstraint::default(),
+            dims: Dimensions::default(),
         }
     }
 
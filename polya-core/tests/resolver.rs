@@ -0,0 +1,149 @@
+// randomized checks of the guarantees the editor relies on: a move either
+// leaves every constraint satisfied or isn't made at all
+
+use polya_core::{
+    math::{Pos2, Vec2},
+    polygon::Polygon,
+    vertex::{EdgeConstraint, VertexConstraint},
+};
+use std::f64::consts;
+
+const CASES: u64 = 300;
+const MOVES: usize = 20;
+
+fn all_satisfied(polygon: &Polygon) -> bool {
+    (0..polygon.vertices.len()).all(|v_i| polygon.check_constraint(v_i))
+}
+
+fn random_point(rng: &mut fastrand::Rng) -> Pos2 {
+    Pos2::new(rng.f64() * 800.0, rng.f64() * 600.0)
+}
+
+// a star-shaped outline around the middle of the canvas
+fn random_outline(rng: &mut fastrand::Rng) -> Polygon {
+    let n = rng.usize(3..=8);
+    let center = Pos2::new(400.0, 300.0);
+    let points: Vec<Pos2> = (0..n)
+        .map(|k| {
+            let angle = (k as f64 + 0.8 * rng.f64()) * consts::TAU / n as f64;
+            center + (100.0 + 200.0 * rng.f64()) * Vec2::angled(angle)
+        })
+        .collect();
+
+    Polygon::from_points(&points, false)
+}
+
+fn random_edge_constraint(rng: &mut fastrand::Rng, len: f64) -> EdgeConstraint {
+    match rng.u8(0..5) {
+        0 => EdgeConstraint::Vertical,
+        1 => EdgeConstraint::Horizontal,
+        2 => EdgeConstraint::DiagonalUp,
+        3 => EdgeConstraint::DiagonalDown,
+        _ => EdgeConstraint::FixedLength(len),
+    }
+}
+
+// an outline with some curved edges and constraints, all of them satisfied
+fn random_polygon(rng: &mut fastrand::Rng) -> Option<Polygon> {
+    let mut polygon = random_outline(rng);
+    polygon.reject_self_intersections = rng.bool();
+    let n = polygon.vertices.len();
+    for e_i in 0..n {
+        match rng.u8(0..6) {
+            0 => polygon.init_bezier(e_i),
+            1 => polygon.make_arc(e_i),
+            _ => (),
+        }
+    }
+    for v_i in 0..n {
+        let vertex_c = match rng.u8(0..5) {
+            0 => VertexConstraint::G0,
+            1 if polygon.can_be_g1(v_i) => VertexConstraint::G1,
+            2 if polygon.can_be_c1(v_i) => VertexConstraint::C1,
+            3 if polygon.can_be_g2(v_i) => VertexConstraint::G2,
+            4 if polygon.can_be_c2(v_i) => VertexConstraint::C2,
+            _ => continue,
+        };
+        polygon.try_set_vertex_constraint(v_i, vertex_c);
+    }
+    for e_i in 0..n {
+        let v = polygon.vertices[e_i];
+        if v.bezier.is_none() && v.arc.is_none() && rng.bool() {
+            let edge_c = random_edge_constraint(rng, polygon.edge_len(e_i).max(1.0));
+            polygon.try_set_edge_constraint(e_i, edge_c);
+        }
+    }
+
+    (polygon.resolve_constraints(0) && all_satisfied(&polygon)).then_some(polygon)
+}
+
+#[test]
+fn moving_vertices_keeps_constraints_or_changes_nothing() {
+    for seed in 0..CASES {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let Some(mut polygon) = random_polygon(&mut rng) else {
+            continue;
+        };
+        for _ in 0..MOVES {
+            let v_i = rng.usize(0..polygon.vertices.len());
+            // mostly small drags, sometimes a jump across the canvas
+            let new_p = if rng.u8(0..4) == 0 {
+                random_point(&mut rng)
+            } else {
+                polygon.vertices[v_i].p + Vec2::new(rng.f64() - 0.5, rng.f64() - 0.5) * 60.0
+            };
+            let before = polygon.vertices.clone();
+            if polygon.try_move_vertex(v_i, new_p) {
+                assert!(all_satisfied(&polygon), "seed {seed}: constraints broken");
+            } else {
+                assert_eq!(
+                    polygon.vertices, before,
+                    "seed {seed}: failed move changed vertices"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn moving_control_vertices_keeps_constraints_or_changes_nothing() {
+    for seed in 0..CASES {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let Some(mut polygon) = random_polygon(&mut rng) else {
+            continue;
+        };
+        let curves: Vec<usize> = (0..polygon.vertices.len())
+            .filter(|&e_i| polygon.is_bezier_start(e_i))
+            .collect();
+        if curves.is_empty() {
+            continue;
+        }
+        for _ in 0..MOVES {
+            let e_i = curves[rng.usize(0..curves.len())];
+            let which = rng.usize(0..2);
+            let new_p = polygon.vertices[e_i].bezier.unwrap().control[which]
+                + Vec2::new(rng.f64() - 0.5, rng.f64() - 0.5) * 60.0;
+            let before = polygon.vertices.clone();
+            if polygon.try_move_control_vertex(e_i, which, new_p) {
+                assert!(all_satisfied(&polygon), "seed {seed}: constraints broken");
+            } else {
+                assert_eq!(
+                    polygon.vertices, before,
+                    "seed {seed}: failed move changed vertices"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn moving_the_whole_polygon_keeps_constraints() {
+    for seed in 0..CASES {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let Some(mut polygon) = random_polygon(&mut rng) else {
+            continue;
+        };
+        polygon.move_polygon(Vec2::new(rng.f64() - 0.5, rng.f64() - 0.5) * 200.0);
+        assert!(all_satisfied(&polygon), "seed {seed}: constraints broken");
+    }
+}
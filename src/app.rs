@@ -1,20 +1,66 @@
 use eframe::egui;
 
+use polya_core::{
+    boolean::BooleanOp,
+    dimension,
+    hull::Convexity,
+    intersection,
+    math::Vec2,
+    offset::JoinStyle,
+    primitives::{CurveKind, Primitive},
+    properties::Properties,
+    simplify::{self, SimplifyAlgorithm},
+    transform::Transform,
+    units::{Unit, Units},
+};
+
 use crate::{
-    constants,
+    constants, dialog,
+    keymap::{Action, Keymap},
     render::{self, LineAlgorithm},
-    state::{CreatingState, EditingState, StateTransition},
+    state::{CreatingState, CreationMode, EditingState, SketchOptions, StateTransition},
 };
 
 #[derive(Debug)]
 enum AppState {
     Creating(CreatingState),
-    Editing(EditingState),
+    // boxed, since the editing state is much larger
+    Editing(Box<EditingState>),
 }
 
 pub struct App {
     state: AppState,
+    // what lengths are shown and exported in
+    units: Units,
+    keymap: Keymap,
+    show_help: bool,
     line_algo: LineAlgorithm,
+    // max distance between a curve and the polyline approximating it
+    flattening_tolerance: f64,
+    show_combs: bool,
+    comb_scale: f64,
+    reject_self_intersections: bool,
+    offset_distance: f64,
+    join_style: JoinStyle,
+    show_transform_handles: bool,
+    // drop directional constraints that transforms break instead of refusing the transform
+    release_constraints: bool,
+    // in degrees
+    rotation_angle: f64,
+    scale: Vec2,
+    show_triangulation: bool,
+    delaunay: bool,
+    show_hull: bool,
+    simplify_algo: SimplifyAlgorithm,
+    simplify_tolerance: f64,
+    preview_simplification: bool,
+    fit_tolerance: f64,
+    creation_mode: CreationMode,
+    // fit Bézier curves to freehand strokes
+    fit_sketch: bool,
+    polygon_sides: usize,
+    circle_kind: CurveKind,
+    corner_radius: f64,
 }
 
 impl Default for App {
@@ -22,8 +68,33 @@ impl Default for App {
         let e_state = EditingState::new_predefined();
 
         Self {
-            state: AppState::Editing(e_state),
+            state: AppState::Editing(Box::new(e_state)),
+            units: Units::default(),
+            keymap: Keymap::load(),
+            show_help: false,
             line_algo: LineAlgorithm::default(),
+            flattening_tolerance: constants::FLATTENING_TOLERANCE,
+            show_combs: false,
+            comb_scale: constants::COMB_SCALE,
+            reject_self_intersections: false,
+            offset_distance: constants::OFFSET_DISTANCE,
+            join_style: JoinStyle::default(),
+            show_transform_handles: false,
+            release_constraints: false,
+            rotation_angle: constants::ROTATION_ANGLE,
+            scale: Vec2::splat(constants::SCALE_FACTOR),
+            show_triangulation: false,
+            delaunay: true,
+            show_hull: false,
+            simplify_algo: SimplifyAlgorithm::default(),
+            simplify_tolerance: constants::SIMPLIFY_TOLERANCE,
+            preview_simplification: false,
+            fit_tolerance: constants::FIT_TOLERANCE,
+            creation_mode: CreationMode::default(),
+            fit_sketch: true,
+            polygon_sides: constants::POLYGON_SIDES,
+            circle_kind: CurveKind::default(),
+            corner_radius: constants::CORNER_RADIUS,
         }
     }
 }
@@ -31,6 +102,46 @@ impl Default for App {
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _: &mut eframe::Frame) {
         ctx.set_zoom_factor(1.5);
+        // typing into a text field shouldn't trigger anything
+        let actions = if ctx.wants_keyboard_input() {
+            Vec::new()
+        } else {
+            self.keymap.pressed(ctx)
+        };
+        for action in &actions {
+            match action {
+                Action::ToggleHelp => self.show_help = !self.show_help,
+                Action::NewShape if matches!(self.state, AppState::Editing(_)) => self.new_shape(),
+                Action::DeleteShape => self.delete_shape(),
+                Action::Reset => self.reset(),
+                _ => (),
+            }
+        }
+        self.render_help(ctx);
+        let intersections = match &self.state {
+            AppState::Editing(e_state) => intersection::self_intersections(&e_state.polygon),
+            AppState::Creating(_) => Vec::new(),
+        };
+        let convexity = match &self.state {
+            AppState::Editing(e_state) => {
+                Some(Convexity::of(&e_state.polygon, self.flattening_tolerance))
+            }
+            AppState::Creating(_) => None,
+        };
+        let kept = match &self.state {
+            AppState::Editing(e_state) => Some(simplify::simplify(
+                &e_state.polygon,
+                self.simplify_algo,
+                self.simplify_tolerance,
+            )),
+            AppState::Creating(_) => None,
+        };
+        let mesh = match &self.state {
+            AppState::Editing(e_state) if self.show_triangulation => {
+                Some(e_state.triangulate(self.delaunay, self.flattening_tolerance))
+            }
+            _ => None,
+        };
         egui::SidePanel::left(constants::ID_SIDEBAR_LEFT)
             .resizable(false)
             .frame(
@@ -43,65 +154,564 @@ impl eframe::App for App {
                 ui.radio_value(&mut self.line_algo, LineAlgorithm::Default, "Default");
                 ui.radio_value(&mut self.line_algo, LineAlgorithm::Bresenham, "Bresenham");
                 ui.separator();
+                ui.label("Units");
+                ui.horizontal(|ui| {
+                    for unit in Unit::ALL {
+                        if ui
+                            .selectable_label(self.units.unit == unit, unit.name())
+                            .clicked()
+                        {
+                            self.units = Units::new(unit);
+                        }
+                    }
+                });
+                ui.add(
+                    egui::DragValue::new(&mut self.units.scale)
+                        .speed(0.01)
+                        .range(constants::MIN_UNIT_SCALE..=f64::INFINITY)
+                        .suffix(format!(" points per {}", self.units.unit)),
+                );
+                ui.separator();
+                ui.label("Curve tolerance");
+                ui.add(
+                    egui::Slider::new(
+                        &mut self.flattening_tolerance,
+                        constants::MIN_FLATTENING_TOLERANCE..=constants::MAX_FLATTENING_TOLERANCE,
+                    )
+                    .logarithmic(true),
+                );
+                ui.separator();
+                ui.checkbox(&mut self.show_combs, "Curvature combs");
+                ui.add_enabled(
+                    self.show_combs,
+                    egui::Slider::new(
+                        &mut self.comb_scale,
+                        constants::MIN_COMB_SCALE..=constants::MAX_COMB_SCALE,
+                    )
+                    .logarithmic(true),
+                );
+                ui.separator();
+                if let AppState::Creating(_) = self.state {
+                    ui.label("Creation mode");
+                    ui.horizontal_wrapped(|ui| {
+                        for (mode, name) in [
+                            (CreationMode::Click, "Click"),
+                            (CreationMode::Freehand, "Freehand"),
+                            (CreationMode::Rectangle, "Rectangle"),
+                            (CreationMode::RegularPolygon, "Regular polygon"),
+                            (CreationMode::Circle, "Circle"),
+                            (CreationMode::RoundedRectangle, "Rounded rectangle"),
+                        ] {
+                            ui.radio_value(&mut self.creation_mode, mode, name);
+                        }
+                    });
+                    match self.creation_mode {
+                        CreationMode::Click | CreationMode::Rectangle => (),
+                        CreationMode::Freehand => {
+                            ui.add(
+                                egui::Slider::new(
+                                    &mut self.simplify_tolerance,
+                                    0.0..=constants::MAX_SIMPLIFY_TOLERANCE,
+                                )
+                                .text("simplify"),
+                            );
+                            ui.checkbox(&mut self.fit_sketch, "Fit Bézier curves");
+                            ui.add_enabled(
+                                self.fit_sketch,
+                                egui::Slider::new(
+                                    &mut self.fit_tolerance,
+                                    constants::MIN_FLATTENING_TOLERANCE
+                                        ..=constants::MAX_FIT_TOLERANCE,
+                                )
+                                .logarithmic(true)
+                                .text("tolerance"),
+                            );
+                        }
+                        CreationMode::RegularPolygon => {
+                            ui.add(
+                                egui::DragValue::new(&mut self.polygon_sides)
+                                    .range(3..=constants::MAX_POLYGON_SIDES)
+                                    .suffix(" sides"),
+                            );
+                        }
+                        CreationMode::Circle => {
+                            ui.horizontal(|ui| {
+                                ui.radio_value(&mut self.circle_kind, CurveKind::Arc, "Arcs");
+                                ui.radio_value(
+                                    &mut self.circle_kind,
+                                    CurveKind::Bezier,
+                                    "Bézier curves",
+                                );
+                            });
+                        }
+                        CreationMode::RoundedRectangle => {
+                            ui.add(
+                                dialog::length_value(
+                                    &mut self.corner_radius,
+                                    self.units,
+                                    0.0..=f64::INFINITY,
+                                )
+                                .prefix("corner radius: "),
+                            );
+                        }
+                    }
+                    ui.separator();
+                }
+                if let AppState::Editing(e_state) = &self.state {
+                    let props = Properties::of(&e_state.polygon);
+                    let bbox = props.bounding_box;
+                    ui.label("Properties");
+                    ui.weak(format!(
+                        "Area: {}",
+                        self.units.format_area(props.signed_area)
+                    ));
+                    ui.weak(format!("Perimeter: {}", self.units.format(props.perimeter)));
+                    ui.weak(format!(
+                        "Centroid: ({}, {})",
+                        self.units.format(props.centroid.x),
+                        self.units.format(props.centroid.y)
+                    ));
+                    ui.weak(format!(
+                        "Bounding box: {} x {} at ({}, {})",
+                        self.units.format(bbox.width()),
+                        self.units.format(bbox.height()),
+                        self.units.format(bbox.min.x),
+                        self.units.format(bbox.min.y)
+                    ));
+                    ui.weak(format!(
+                        "Self-intersecting: {}",
+                        if intersections.is_empty() {
+                            "no"
+                        } else {
+                            "yes"
+                        }
+                    ));
+                    ui.separator();
+                }
+                if let Some(convexity) = &convexity {
+                    ui.checkbox(&mut self.show_hull, "Convex hull");
+                    ui.weak(format!(
+                        "Convex: {}",
+                        if convexity.is_convex { "yes" } else { "no" }
+                    ));
+                    if !convexity.reflex_vertices.is_empty() {
+                        ui.weak(format!(
+                            "Reflex vertices: {}",
+                            convexity
+                                .reflex_vertices
+                                .iter()
+                                .map(|v_i| (v_i + 1).to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ));
+                    }
+                    if let AppState::Editing(e_state) = &mut self.state
+                        && ui.button("Replace with hull").clicked()
+                    {
+                        e_state.replace_with_hull(self.flattening_tolerance);
+                    }
+                    ui.separator();
+                }
+                ui.checkbox(&mut self.show_triangulation, "Triangulation");
+                ui.add_enabled(
+                    self.show_triangulation,
+                    egui::Checkbox::new(&mut self.delaunay, "Constrained Delaunay"),
+                );
+                match &mesh {
+                    Some(Ok(mesh)) => {
+                        ui.weak(format!("Triangles: {}", mesh.triangles.len()));
+                        if ui.button("Copy as OBJ").clicked() {
+                            ctx.copy_text(mesh.to_obj(self.units));
+                        }
+                    }
+                    Some(Err(e)) => {
+                        ui.colored_label(
+                            constants::COLOR_EDGE_SEC,
+                            format!("Can't triangulate: {e}"),
+                        );
+                    }
+                    None => (),
+                }
+                ui.separator();
+                ui.checkbox(
+                    &mut self.reject_self_intersections,
+                    "Reject self-intersections",
+                );
+                ui.separator();
+                if let AppState::Editing(e_state) = &mut self.state {
+                    ui.label("Transform");
+                    ui.checkbox(&mut self.show_transform_handles, "Handles");
+                    ui.checkbox(
+                        &mut self.release_constraints,
+                        "Release direction constraints",
+                    );
+                    let pivot = e_state.pivot();
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut self.rotation_angle)
+                                .speed(1.0)
+                                .range(-360.0..=360.0)
+                                .suffix("°"),
+                        );
+                        if ui.button("Rotate").clicked() {
+                            e_state.try_transform(
+                                Transform::rotation(pivot, self.rotation_angle.to_radians()),
+                                self.release_constraints,
+                            );
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut self.scale.x)
+                                .speed(0.01)
+                                .prefix("x: "),
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut self.scale.y)
+                                .speed(0.01)
+                                .prefix("y: "),
+                        );
+                        if ui.button("Scale").clicked()
+                            && self.scale.x.abs() >= constants::MIN_SCALE
+                            && self.scale.y.abs() >= constants::MIN_SCALE
+                        {
+                            e_state.try_transform(
+                                Transform::scale(pivot, self.scale.x, self.scale.y),
+                                self.release_constraints,
+                            );
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        if ui.button("Mirror ↔").clicked() {
+                            e_state.try_transform(
+                                Transform::scale(pivot, -1.0, 1.0),
+                                self.release_constraints,
+                            );
+                        }
+                        if ui.button("Mirror ↕").clicked() {
+                            e_state.try_transform(
+                                Transform::scale(pivot, 1.0, -1.0),
+                                self.release_constraints,
+                            );
+                        }
+                        if ui.button("Pivot at centroid").clicked() {
+                            e_state.pivot_offset = Vec2::ZERO;
+                        }
+                    });
+                    ui.separator();
+                    ui.label("Simplify");
+                    ui.horizontal(|ui| {
+                        ui.radio_value(
+                            &mut self.simplify_algo,
+                            SimplifyAlgorithm::DouglasPeucker,
+                            "Douglas-Peucker",
+                        );
+                        ui.radio_value(
+                            &mut self.simplify_algo,
+                            SimplifyAlgorithm::Visvalingam,
+                            "Visvalingam",
+                        );
+                    });
+                    ui.add(
+                        egui::Slider::new(
+                            &mut self.simplify_tolerance,
+                            0.0..=constants::MAX_SIMPLIFY_TOLERANCE,
+                        )
+                        .text("tolerance"),
+                    );
+                    ui.checkbox(&mut self.preview_simplification, "Preview");
+                    if let Some(kept) = &kept {
+                        ui.weak(format!(
+                            "Vertices: {} -> {}",
+                            e_state.polygon.vertices.len(),
+                            kept.len()
+                        ));
+                    }
+                    if ui.button("Simplify").clicked() {
+                        e_state.apply_simplification(self.simplify_algo, self.simplify_tolerance);
+                    }
+                    ui.separator();
+                    ui.label("Fit Bézier curves");
+                    match e_state.chain() {
+                        Some((start_i, end_i)) if start_i == end_i => {
+                            ui.weak(format!("Chain: whole outline from {}", start_i + 1));
+                        }
+                        Some((start_i, end_i)) => {
+                            ui.weak(format!("Chain: {} to {}", start_i + 1, end_i + 1));
+                        }
+                        None => {
+                            ui.weak("Pick the ends of a chain with RMB on vertices");
+                        }
+                    }
+                    ui.add(
+                        egui::Slider::new(
+                            &mut self.fit_tolerance,
+                            constants::MIN_FLATTENING_TOLERANCE..=constants::MAX_FIT_TOLERANCE,
+                        )
+                        .logarithmic(true)
+                        .text("tolerance"),
+                    );
+                    ui.horizontal(|ui| {
+                        if ui.button("Whole outline").clicked() {
+                            e_state.chain_start_i = Some(0);
+                            e_state.chain_end_i = Some(0);
+                        }
+                        if ui
+                            .add_enabled(e_state.chain().is_some(), egui::Button::new("Fit"))
+                            .clicked()
+                        {
+                            e_state.fit_beziers(self.fit_tolerance);
+                        }
+                    });
+                    ui.separator();
+                    ui.label("Offset (negative to inset)");
+                    ui.add(dialog::length_value(
+                        &mut self.offset_distance,
+                        self.units,
+                        f64::NEG_INFINITY..=f64::INFINITY,
+                    ));
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut self.join_style, JoinStyle::Miter, "Miter");
+                        ui.radio_value(&mut self.join_style, JoinStyle::Round, "Round");
+                        ui.radio_value(&mut self.join_style, JoinStyle::Bevel, "Bevel");
+                    });
+                    if ui.button("Offset").clicked() {
+                        e_state.apply_offset(
+                            self.offset_distance,
+                            self.join_style,
+                            self.flattening_tolerance,
+                        );
+                    }
+                    ui.separator();
+                }
+                if let AppState::Editing(e_state) = &mut self.state
+                    && !e_state.shapes.is_empty()
+                {
+                    ui.label("Boolean operations");
+                    egui::ComboBox::from_id_salt(constants::ID_OPERAND_COMBO)
+                        .selected_text(format!("with shape {}", e_state.operand_i + 1))
+                        .show_ui(ui, |ui| {
+                            for i in 0..e_state.shapes.len() {
+                                ui.selectable_value(
+                                    &mut e_state.operand_i,
+                                    i,
+                                    format!("with shape {}", i + 1),
+                                );
+                            }
+                        });
+                    ui.horizontal_wrapped(|ui| {
+                        for (op, name) in [
+                            (BooleanOp::Union, "Union"),
+                            (BooleanOp::Intersection, "Intersection"),
+                            (BooleanOp::Difference, "Difference"),
+                            (BooleanOp::Xor, "XOR"),
+                        ] {
+                            if ui.button(name).clicked() {
+                                e_state.apply_boolean(op, self.flattening_tolerance);
+                            }
+                        }
+                    });
+                    ui.separator();
+                }
                 ui.vertical_centered(|ui| {
+                    if let AppState::Editing(_) = self.state {
+                        if ui.button("New shape").clicked() {
+                            self.new_shape();
+                        }
+                        if ui.button("Delete shape").clicked() {
+                            self.delete_shape();
+                        }
+                    }
                     if ui.button("Reset").clicked() {
-                        *self = Self::reset(self.line_algo);
+                        self.reset();
                     }
                 });
             });
 
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.style_mut().interaction.selectable_labels = false;
-            match self.state {
+            match &self.state {
                 AppState::Creating(_) => {
                     ui.vertical_centered(|ui| {
                         ui.heading("Create a polygon to start editing it");
-                        ui.weak("Add vertices with LMB");
+                        match self.creation_mode {
+                            CreationMode::Click => ui.weak("Add vertices with LMB"),
+                            CreationMode::Freehand => {
+                                ui.weak("Draw holding LMB and end the stroke at its start")
+                            }
+                            _ => ui.weak("Drag the shape out with LMB"),
+                        };
                     });
                 }
-                AppState::Editing(_) => {
+                AppState::Editing(e_state) => {
                     ui.vertical_centered(|ui| {
                         ui.heading("Edit the polygon and move it around");
                         ui.weak("Move vertices and Bézier control points by dragging");
                         ui.weak("Move the entire polygon instead by holding [Shift]");
                         ui.weak("Toggle constraints with RMB on vertex/edge");
+                        ui.weak("Insert a vertex by double-clicking an edge");
+                        ui.weak("Switch to another shape by clicking on it");
+                        ui.weak("Snap rotations with the handle by holding [Shift]");
+                        if let Some(shortcut) = self.keymap.shortcut(Action::ToggleHelp) {
+                            ui.weak(format!(
+                                "List keyboard shortcuts with [{}]",
+                                ctx.format_shortcut(&shortcut)
+                            ));
+                        }
+                        if let Some(notice) = &e_state.notice {
+                            ui.colored_label(constants::COLOR_EDGE_LABEL, notice);
+                        }
                     });
                 }
             }
+            let primitive = self.primitive();
             let painter = ui.painter();
             match &mut self.state {
                 AppState::Creating(c_state) => {
+                    for shape in c_state.shapes.iter() {
+                        render::render_shape(
+                            painter,
+                            shape,
+                            constants::COLOR_SHAPE,
+                            self.flattening_tolerance,
+                        );
+                    }
                     render::render_polyline_edges(painter, &c_state.vertices, self.line_algo);
-                    render::render_vertices(painter, &c_state.vertices, Some(0), false);
+                    // the samples of a freehand stroke are too dense to show
+                    let shown = match self.creation_mode {
+                        CreationMode::Click => c_state.vertices.len(),
+                        _ => c_state.vertices.len().min(1),
+                    };
+                    render::render_vertices(painter, &c_state.vertices[..shown], Some(0), false);
+                    if let (Some(primitive), Some((start, end))) = (primitive, c_state.drag)
+                        && let Some(polygon) = primitive.build(start, end)
+                    {
+                        render::render_shape(
+                            painter,
+                            &polygon,
+                            constants::COLOR_EDGE_PRI,
+                            self.flattening_tolerance,
+                        );
+                    }
 
-                    if let Some(trans) = c_state.handle_add_point(ctx, ui.min_rect())
+                    let transition = match (self.creation_mode, primitive) {
+                        (_, Some(primitive)) => {
+                            c_state.handle_drag_primitive(ctx, ui.min_rect().into(), primitive)
+                        }
+                        (CreationMode::Click, _) => {
+                            c_state.handle_add_point(ctx, ui.min_rect().into())
+                        }
+                        (_, None) => c_state.handle_sketch(
+                            ctx,
+                            ui.min_rect().into(),
+                            SketchOptions {
+                                simplify_tolerance: self.simplify_tolerance,
+                                fit_tolerance: self.fit_sketch.then_some(self.fit_tolerance),
+                            },
+                        ),
+                    };
+                    if let Some(trans) = transition
                         && let StateTransition::ToEditing = trans
                     {
-                        self.state = AppState::Editing(EditingState::new(std::mem::take(
-                            &mut c_state.vertices,
+                        self.state = AppState::Editing(Box::new(EditingState::new(
+                            std::mem::take(&mut c_state.vertices),
+                            std::mem::take(&mut c_state.shapes),
                         )));
                     }
                 }
                 AppState::Editing(e_state) => {
+                    for (i, shape) in e_state.shapes.iter().enumerate() {
+                        let color = if i == e_state.operand_i {
+                            constants::COLOR_OPERAND
+                        } else {
+                            constants::COLOR_SHAPE
+                        };
+                        render::render_shape(painter, shape, color, self.flattening_tolerance);
+                    }
+                    if let Some(Ok(mesh)) = &mesh {
+                        render::render_mesh(painter, mesh);
+                    }
                     render::render_polygon_edges(
                         painter,
                         &e_state.polygon.vertices,
                         e_state.selected_edge_i,
                         self.line_algo,
+                        self.flattening_tolerance,
+                        self.units,
                     );
+                    render::render_annotations(
+                        painter,
+                        &dimension::annotations(
+                            &e_state.polygon,
+                            self.units,
+                            self.flattening_tolerance,
+                        ),
+                    );
+                    if self.show_combs {
+                        render::render_curvature_combs(
+                            painter,
+                            &e_state.polygon.vertices,
+                            self.comb_scale,
+                        );
+                    }
                     render::render_vertices(
                         painter,
                         &e_state.polygon.vertices,
                         e_state.selected_vertex_i,
                         true,
                     );
+                    render::render_intersections(painter, &intersections);
+                    if self.show_hull
+                        && let Some(convexity) = &convexity
+                    {
+                        render::render_convexity(
+                            painter,
+                            convexity,
+                            &e_state.polygon.vertices,
+                            self.flattening_tolerance,
+                        );
+                    }
+                    if let Some((start_i, end_i)) = e_state.chain() {
+                        render::render_chain(
+                            painter,
+                            &e_state.polygon,
+                            start_i,
+                            end_i,
+                            self.flattening_tolerance,
+                        );
+                    }
+                    if self.preview_simplification
+                        && let Some(kept) = &kept
+                    {
+                        render::render_simplification(
+                            painter,
+                            &e_state.polygon,
+                            kept,
+                            self.flattening_tolerance,
+                        );
+                    }
+                    if self.show_transform_handles {
+                        render::render_transform_handles(
+                            painter,
+                            Properties::of(&e_state.polygon).bounding_box,
+                            e_state.pivot(),
+                        );
+                    }
 
+                    e_state.polygon.reject_self_intersections = self.reject_self_intersections;
+
+                    if self.show_transform_handles {
+                        e_state.handle_transform(ctx, self.release_constraints);
+                    }
+                    e_state.handle_activate(ctx, self.flattening_tolerance);
                     e_state.handle_drag_vertex(ctx);
                     e_state.handle_drag_polygon(ctx);
-                    e_state.handle_select(ctx);
+                    e_state.handle_insert_vertex(ctx, self.flattening_tolerance);
+                    e_state.handle_select(ctx, self.flattening_tolerance);
                     // handle_select before doing actions that depend on the current selection
-                    e_state.handle_vertex_context_menu(ctx);
-                    e_state.handle_edge_context_menu(ctx);
+                    e_state.handle_shortcuts(ctx, &actions, self.flattening_tolerance);
+                    e_state.handle_vertex_context_menu(ctx, self.units, &self.keymap);
+                    e_state.handle_edge_context_menu(ctx, self.units, &self.keymap);
                 }
             }
         });
@@ -109,12 +719,63 @@ impl eframe::App for App {
 }
 
 impl App {
-    fn reset(line_algo: LineAlgorithm) -> Self {
-        let c_state = CreatingState::new();
+    // the shape dragged out in the current creation mode, if any
+    fn primitive(&self) -> Option<Primitive> {
+        match self.creation_mode {
+            CreationMode::Click | CreationMode::Freehand => None,
+            CreationMode::Rectangle => Some(Primitive::Rectangle),
+            CreationMode::RegularPolygon => Some(Primitive::RegularPolygon(self.polygon_sides)),
+            CreationMode::Circle => Some(Primitive::Circle(self.circle_kind)),
+            CreationMode::RoundedRectangle => Some(Primitive::RoundedRectangle(self.corner_radius)),
+        }
+    }
 
-        Self {
-            state: AppState::Creating(c_state),
-            line_algo,
+    fn render_help(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Keyboard shortcuts")
+            .open(&mut self.show_help)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.weak("Vertex and edge actions apply to the selection,");
+                ui.weak("or to the vertex or edge under the pointer");
+                ui.separator();
+                egui::Grid::new(constants::ID_HELP_GRID)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for action in self.keymap.actions() {
+                            ui.label(action.description());
+                            match self.keymap.shortcut(action) {
+                                Some(shortcut) => ui.monospace(ctx.format_shortcut(&shortcut)),
+                                None => ui.weak("none"),
+                            };
+                            ui.weak(action.name());
+                            ui.end_row();
+                        }
+                    });
+                if let Some(path) = Keymap::path() {
+                    ui.separator();
+                    ui.weak(format!("Rebind them in {}", path.display()));
+                }
+            });
+    }
+
+    fn reset(&mut self) {
+        self.state = AppState::Creating(CreatingState::new());
+    }
+
+    // start creating another shape, keeping the existing ones
+    fn new_shape(&mut self) {
+        let state = std::mem::replace(&mut self.state, AppState::Creating(CreatingState::new()));
+        if let AppState::Editing(e_state) = state {
+            self.state = AppState::Creating(CreatingState::with_shapes(e_state.into_shapes()));
+        }
+    }
+
+    fn delete_shape(&mut self) {
+        if let AppState::Editing(e_state) = &mut self.state
+            && !e_state.delete_polygon()
+        {
+            self.reset();
         }
     }
 }
@@ -0,0 +1,159 @@
+use std::{fs, path::Path, process::ExitCode};
+
+use polya_core::{
+    document::{self, Document},
+    intersection,
+    polygon::Polygon,
+    properties::Properties,
+};
+
+use crate::constants;
+
+pub const COMMANDS: [&str; 3] = ["convert", "info", "validate"];
+
+const USAGE: &str = "usage:
+  polya info <file.polya>
+  polya validate <file.polya>
+  polya convert <in.polya> <out.polya|out.svg|out.obj> [--tolerance <t>]";
+
+fn load(path: &str) -> Result<Document, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+    document::from_text(&text).map_err(|e| format!("{path}: {e}"))
+}
+
+// resolves the constraints of every shape, returns whether all of them hold afterwards
+fn resolve(shapes: &mut [Polygon]) -> Vec<bool> {
+    shapes
+        .iter_mut()
+        .map(|polygon| {
+            polygon.resolve_constraints(0);
+            (0..polygon.vertices.len()).all(|v_i| polygon.check_constraint(v_i))
+        })
+        .collect()
+}
+
+// lengths and areas are in the document's units, positions in model units
+fn info(path: &str) -> Result<bool, String> {
+    let mut doc = load(path)?;
+    let units = doc.units;
+    let satisfied = resolve(&mut doc.shapes);
+    println!("units: {} ({} model units each)", units.unit, units.scale);
+    for (shape_i, (polygon, &ok)) in doc.shapes.iter().zip(satisfied.iter()).enumerate() {
+        let props = Properties::of(polygon);
+        let bbox = props.bounding_box;
+        println!("{} {shape_i}", if polygon.hole { "hole" } else { "shape" });
+        println!("  vertices:      {}", polygon.vertices.len());
+        println!(
+            "  area:          {}",
+            units.format_area(props.signed_area.abs())
+        );
+        println!("  perimeter:     {}", units.format(props.perimeter));
+        println!(
+            "  centroid:      ({}, {})",
+            units.format(props.centroid.x),
+            units.format(props.centroid.y)
+        );
+        println!(
+            "  bounding box:  {} x {} at ({}, {})",
+            units.format(bbox.width()),
+            units.format(bbox.height()),
+            units.format(bbox.min.x),
+            units.format(bbox.min.y)
+        );
+        println!(
+            "  constraints:   {}",
+            if ok { "satisfied" } else { "not satisfied" }
+        );
+        println!(
+            "  intersections: {}",
+            intersection::self_intersections(polygon).len()
+        );
+    }
+
+    Ok(true)
+}
+
+// a document is valid if all constraints can be satisfied and no outline crosses itself
+fn validate(path: &str) -> Result<bool, String> {
+    let mut doc = load(path)?;
+    let satisfied = resolve(&mut doc.shapes);
+    let mut valid = true;
+    for (shape_i, (polygon, &ok)) in doc.shapes.iter().zip(satisfied.iter()).enumerate() {
+        if !ok {
+            println!("shape {shape_i}: constraints can't be satisfied");
+            valid = false;
+        }
+        if polygon.is_self_intersecting() {
+            println!("shape {shape_i}: the outline intersects itself");
+            valid = false;
+        }
+    }
+    if valid {
+        println!("{path}: ok");
+    }
+
+    Ok(valid)
+}
+
+fn convert(input: &str, output: &str, tolerance: f64) -> Result<bool, String> {
+    let mut doc = load(input)?;
+    if resolve(&mut doc.shapes).contains(&false) {
+        eprintln!("warning: not all constraints could be satisfied");
+    }
+    let extension = Path::new(output)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    let contents = match extension.as_str() {
+        "polya" => document::to_text(&doc),
+        "svg" => document::to_svg(&doc, tolerance).map_err(|e| format!("can't export: {e}"))?,
+        "obj" => document::to_mesh(&doc, tolerance)
+            .map_err(|e| format!("can't triangulate: {e}"))?
+            .to_obj(doc.units),
+        _ => return Err(format!("{output}: unsupported format `{extension}`")),
+    };
+    fs::write(output, contents).map_err(|e| format!("{output}: {e}"))?;
+
+    Ok(true)
+}
+
+fn parse_tolerance(args: &[String]) -> Result<(Vec<&str>, f64), String> {
+    let mut rest = Vec::new();
+    let mut tolerance = constants::FLATTENING_TOLERANCE;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--tolerance" {
+            tolerance = args
+                .next()
+                .and_then(|t| t.parse::<f64>().ok())
+                .filter(|&t| t >= constants::MIN_FLATTENING_TOLERANCE)
+                .ok_or(format!(
+                    "--tolerance needs a number of at least {}",
+                    constants::MIN_FLATTENING_TOLERANCE
+                ))?;
+        } else {
+            rest.push(arg.as_str());
+        }
+    }
+
+    Ok((rest, tolerance))
+}
+
+// runs a command without opening a window, args don't include the program name
+pub fn run(args: &[String]) -> ExitCode {
+    let result = parse_tolerance(args).and_then(|(args, tolerance)| match args[..] {
+        ["info", path] => info(path),
+        ["validate", path] => validate(path),
+        ["convert", input, output] => convert(input, output, tolerance),
+        _ => Err(USAGE.to_string()),
+    });
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::from(2)
+        }
+    }
+}
@@ -1,9 +1,16 @@
 use egui::Color32;
 
+pub use polya_core::constants::*;
+
 pub const ID_SIDEBAR_LEFT: &str = "sidebar_left";
 pub const ID_VERTEX_CONTEXT_MENU: &str = "vertex_context_menu";
 pub const ID_EDGE_CONTEXT_MENU: &str = "edge_context_menu";
 pub const ID_FIXED_LEN_DIALOG: &str = "fixed_len_dialog";
+pub const ID_ARC_DIALOG: &str = "arc_dialog";
+pub const ID_FILLET_DIALOG: &str = "fillet_dialog";
+pub const ID_CHAMFER_DIALOG: &str = "chamfer_dialog";
+pub const ID_OPERAND_COMBO: &str = "operand_combo";
+pub const ID_HELP_GRID: &str = "help_grid";
 
 pub const COLOR_BKG: Color32 = Color32::BLACK;
 pub const COLOR_VERTEX_PRI: Color32 = Color32::WHITE;
@@ -13,25 +20,61 @@ pub const COLOR_EDGE_PRI: Color32 = Color32::WHITE;
 pub const COLOR_EDGE_SEC: Color32 = Color32::RED;
 pub const COLOR_EDGE_LABEL: Color32 = Color32::LIGHT_RED;
 pub const COLOR_VERTEX_LABEL: Color32 = Color32::LIGHT_RED;
+pub const COLOR_SHAPE: Color32 = Color32::GRAY;
+pub const COLOR_OPERAND: Color32 = Color32::LIGHT_GREEN;
+pub const COLOR_COMB: Color32 = Color32::LIGHT_BLUE;
+pub const COLOR_INTERSECTION: Color32 = Color32::YELLOW;
+pub const COLOR_TRANSFORM: Color32 = Color32::GOLD;
+pub const COLOR_MESH: Color32 = Color32::DARK_GRAY;
+pub const COLOR_HULL: Color32 = Color32::MAGENTA;
+pub const COLOR_SIMPLIFIED: Color32 = Color32::ORANGE;
+pub const COLOR_CHAIN: Color32 = Color32::KHAKI;
+pub const COLOR_DIMENSION: Color32 = Color32::LIGHT_YELLOW;
 
 pub const SIZE_STROKE: f32 = 1.0;
-pub const SIZE_VERTEX: f32 = 4.0;
-pub const SIZE_CONTROL_VERTEX: f32 = 6.0;
+pub const SIZE_INTERSECTION_MARKER: f32 = 6.0;
+pub const SIZE_CHAIN_STROKE: f32 = 3.0;
+pub const SIZE_TRANSFORM_HANDLE: f32 = 3.0;
 pub const SIZE_DASHES: f32 = 3.0;
 pub const SIZE_GAPS: f32 = 5.0;
-pub const SIZE_HITRADIUS: f32 = 2.0;
 pub const SIZE_MARGIN: i8 = 15;
 pub const SIZE_CONTEXT_MENU: f32 = 200.0;
 pub const SIZE_CONTEXT_MENU_OFFSET: f32 = 10.0;
-pub const SIZE_MIN_EDGE_LENGTH: f32 = 1.0;
-pub const SIZE_MAX_EDGE_LENGTH: f32 = 1000.0;
 pub const SIZE_LABEL_FONT: f32 = 14.0;
 pub const SIZE_LABEL_OFFSET: f32 = 10.0;
 
-pub const EPS: f32 = 0.01;
-pub const DOT_EPS: f32 = 0.001;
-pub const DIST_EPS: f32 = 0.1;
-pub const BEZIER_DT: f32 = 0.001;
-pub const ARC_DALPHA: f32 = 0.001;
+pub const FLATTENING_TOLERANCE: f64 = 0.25;
+pub const MIN_FLATTENING_TOLERANCE: f64 = 0.05;
+pub const MAX_FLATTENING_TOLERANCE: f64 = 5.0;
+
+pub const COMB_SAMPLES: usize = 48;
+pub const COMB_SCALE: f64 = 2000.0;
+pub const MIN_COMB_SCALE: f64 = 100.0;
+pub const MAX_COMB_SCALE: f64 = 50000.0;
+
+pub const OFFSET_DISTANCE: f64 = 10.0;
+
+pub const SIMPLIFY_TOLERANCE: f64 = 2.0;
+pub const MAX_SIMPLIFY_TOLERANCE: f64 = 50.0;
+
+pub const POLYGON_SIDES: usize = 6;
+pub const MAX_POLYGON_SIDES: usize = 64;
+pub const CORNER_RADIUS: f64 = 20.0;
+pub const CHAMFER_DISTANCE: f64 = 20.0;
+
+// distance between the points sampled from a freehand stroke
+pub const SKETCH_SAMPLE_DIST: f64 = 3.0;
+// how close to its start a stroke has to end to close the shape
+pub const SKETCH_CLOSE_DIST: f64 = 15.0;
+
+pub const FIT_TOLERANCE: f64 = 2.0;
+pub const MAX_FIT_TOLERANCE: f64 = 50.0;
+
+pub const ROTATION_ANGLE: f64 = 90.0;
+pub const SCALE_FACTOR: f64 = 2.0;
+
+pub const MIN_UNIT_SCALE: f64 = 0.001;
 
-pub const MAX_RESOLVING_ITERS: u8 = 64;
+// how far the arrow keys move things, without and with [Shift]
+pub const NUDGE_STEP: f64 = 1.0;
+pub const NUDGE_STEP_FAR: f64 = 10.0;
@@ -1,9 +1,33 @@
+use std::ops::RangeInclusive;
+
+use polya_core::{units::Units, vertex::CircleArc};
+
 use crate::constants;
 
+// edits a length of the model in the document's units, the range is in model units
+pub fn length_value(
+    value: &mut f64,
+    units: Units,
+    range: RangeInclusive<f64>,
+) -> egui::DragValue<'_> {
+    let range = units.to_unit(*range.start())..=units.to_unit(*range.end());
+    egui::DragValue::from_get_set(move |new_value| {
+        if let Some(new_value) = new_value {
+            *value = units.from_unit(new_value);
+        }
+        units.to_unit(*value)
+    })
+    .range(range)
+    .speed(units.to_unit(1.0))
+    .max_decimals(units.unit.precision())
+    .suffix(format!(" {}", units.unit))
+}
+
 #[derive(Debug)]
 pub struct FixedLengthDialog {
     id: egui::Id,
-    pub value: f32,
+    pub value: f64,
+    units: Units,
     pub applied: bool,
 }
 
@@ -12,37 +36,153 @@ impl Default for FixedLengthDialog {
         Self {
             id: constants::ID_FIXED_LEN_DIALOG.into(),
             value: 0.0,
+            units: Units::default(),
             applied: false,
         }
     }
 }
 
 impl FixedLengthDialog {
-    pub fn open(&mut self, ui: &mut egui::Ui, init_value: f32) {
-        ui.memory_mut(|m| m.toggle_popup(self.id));
+    pub fn open(&mut self, ui: &mut egui::Ui, init_value: f64, units: Units) {
+        egui::Popup::toggle_id(ui.ctx(), self.id);
         self.value = init_value;
+        self.units = units;
     }
 
-    pub fn render(&mut self, ui: &mut egui::Ui, response: &egui::Response) {
-        egui::popup_below_widget(
-            ui,
-            self.id,
-            response,
-            egui::PopupCloseBehavior::CloseOnClickOutside,
-            |ui| {
+    pub fn render(&mut self, response: &egui::Response) {
+        egui::Popup::from_response(response)
+            .id(self.id)
+            .open_memory(None)
+            .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside)
+            .show(|ui| {
                 ui.horizontal(|ui| {
                     ui.label("Length:");
-                    ui.add(
-                        egui::DragValue::new(&mut self.value).range(
-                            constants::SIZE_MIN_EDGE_LENGTH..=constants::SIZE_MAX_EDGE_LENGTH,
-                        ),
-                    );
+                    ui.add(length_value(
+                        &mut self.value,
+                        self.units,
+                        constants::SIZE_MIN_EDGE_LENGTH..=f64::INFINITY,
+                    ));
+                });
+                if ui.button("Apply").clicked() {
+                    egui::Popup::close_id(ui.ctx(), self.id);
+                    self.applied = true;
+                }
+            });
+    }
+}
+
+#[derive(Debug)]
+pub struct ArcDialog {
+    id: egui::Id,
+    pub arc: CircleArc,
+    pub radius: f64,
+    min_radius: f64,
+    units: Units,
+    pub applied: bool,
+}
+
+impl Default for ArcDialog {
+    fn default() -> Self {
+        Self {
+            id: constants::ID_ARC_DIALOG.into(),
+            arc: CircleArc::default(),
+            radius: 0.0,
+            min_radius: 0.0,
+            units: Units::default(),
+            applied: false,
+        }
+    }
+}
+
+impl ArcDialog {
+    pub fn open(
+        &mut self,
+        ui: &mut egui::Ui,
+        init_arc: CircleArc,
+        init_radius: f64,
+        min_radius: f64,
+        units: Units,
+    ) {
+        egui::Popup::toggle_id(ui.ctx(), self.id);
+        self.arc = init_arc;
+        self.radius = init_radius;
+        self.min_radius = min_radius;
+        self.units = units;
+    }
+
+    pub fn render(&mut self, response: &egui::Response) {
+        egui::Popup::from_response(response)
+            .id(self.id)
+            .open_memory(None)
+            .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside)
+            .show(|ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Radius:");
+                    ui.add(length_value(
+                        &mut self.radius,
+                        self.units,
+                        self.min_radius..=f64::INFINITY,
+                    ));
+                });
+                ui.checkbox(&mut self.arc.large, "Large arc");
+                ui.checkbox(&mut self.arc.clockwise, "Clockwise");
+                if ui.button("Apply").clicked() {
+                    egui::Popup::close_id(ui.ctx(), self.id);
+                    self.arc.radius = Some(self.radius);
+                    self.applied = true;
+                }
+            });
+    }
+}
+
+// asks for the size of a fillet or chamfer, which can't go past the neighboring vertices
+#[derive(Debug)]
+pub struct CornerDialog {
+    id: egui::Id,
+    label: &'static str,
+    pub value: f64,
+    max_value: f64,
+    units: Units,
+    pub applied: bool,
+}
+
+impl CornerDialog {
+    pub fn new(id: &'static str, label: &'static str) -> Self {
+        Self {
+            id: id.into(),
+            label,
+            value: 0.0,
+            max_value: 0.0,
+            units: Units::default(),
+            applied: false,
+        }
+    }
+
+    pub fn open(&mut self, ui: &mut egui::Ui, init_value: f64, max_value: f64, units: Units) {
+        egui::Popup::toggle_id(ui.ctx(), self.id);
+        self.value = init_value.min(max_value);
+        self.max_value = max_value;
+        self.units = units;
+    }
+
+    pub fn render(&mut self, response: &egui::Response) {
+        egui::Popup::from_response(response)
+            .id(self.id)
+            .open_memory(None)
+            .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside)
+            .show(|ui| {
+                ui.horizontal(|ui| {
+                    ui.label(self.label);
+                    ui.add(length_value(
+                        &mut self.value,
+                        self.units,
+                        constants::SIZE_MIN_EDGE_LENGTH..=self.max_value,
+                    ));
                 });
                 if ui.button("Apply").clicked() {
-                    ui.memory_mut(|m| m.toggle_popup(self.id));
+                    egui::Popup::close_id(ui.ctx(), self.id);
                     self.applied = true;
                 }
-            },
-        );
+            });
     }
 }
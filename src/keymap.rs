@@ -0,0 +1,476 @@
+use std::{env, fmt, fs, io, path::PathBuf};
+
+use egui::{Context, Key, KeyboardShortcut, Modifiers};
+
+// everything that can be done from the keyboard, vertex and edge actions
+// apply to the selected vertex or edge, or the one under the pointer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    RemoveVertex,
+    Fillet,
+    Chamfer,
+    StartChain,
+    EndChain,
+    SetG0,
+    SetG1,
+    SetC1,
+    SetG2,
+    SetC2,
+    MakeVertical,
+    MakeHorizontal,
+    MakeDiagonalUp,
+    MakeDiagonalDown,
+    FixLength,
+    ToBezier,
+    ToArc,
+    Subdivide,
+    RemoveConstraint,
+    SplitCurve,
+    RemoveBezier,
+    SetRadius,
+    ResetRadius,
+    RemoveArc,
+    ToggleLengthDimension,
+    ToggleAngleDimension,
+    ToggleRadiusDimension,
+    NudgeLeft,
+    NudgeRight,
+    NudgeUp,
+    NudgeDown,
+    Deselect,
+    NewShape,
+    DeleteShape,
+    Reset,
+    ToggleHelp,
+}
+
+const fn shortcut(modifiers: Modifiers, key: Key) -> Option<KeyboardShortcut> {
+    Some(KeyboardShortcut::new(modifiers, key))
+}
+
+// name in the keymap file, description in the help overlay, default shortcut
+const ACTIONS: [(Action, &str, &str, Option<KeyboardShortcut>); 36] = [
+    (
+        Action::RemoveVertex,
+        "remove-vertex",
+        "Remove vertex",
+        shortcut(Modifiers::NONE, Key::Delete),
+    ),
+    (
+        Action::Fillet,
+        "fillet",
+        "Fillet corner",
+        shortcut(Modifiers::NONE, Key::F),
+    ),
+    (
+        Action::Chamfer,
+        "chamfer",
+        "Chamfer corner",
+        shortcut(Modifiers::SHIFT, Key::F),
+    ),
+    (
+        Action::StartChain,
+        "start-chain",
+        "Start chain here",
+        shortcut(Modifiers::NONE, Key::OpenBracket),
+    ),
+    (
+        Action::EndChain,
+        "end-chain",
+        "End chain here",
+        shortcut(Modifiers::NONE, Key::CloseBracket),
+    ),
+    (
+        Action::SetG0,
+        "set-g0",
+        "Set G0",
+        shortcut(Modifiers::NONE, Key::Num0),
+    ),
+    (
+        Action::SetG1,
+        "set-g1",
+        "Set G1",
+        shortcut(Modifiers::NONE, Key::Num1),
+    ),
+    (
+        Action::SetC1,
+        "set-c1",
+        "Set C1",
+        shortcut(Modifiers::NONE, Key::C),
+    ),
+    (
+        Action::SetG2,
+        "set-g2",
+        "Set G2",
+        shortcut(Modifiers::NONE, Key::Num2),
+    ),
+    (
+        Action::SetC2,
+        "set-c2",
+        "Set C2",
+        shortcut(Modifiers::SHIFT, Key::C),
+    ),
+    (
+        Action::MakeVertical,
+        "make-vertical",
+        "Make edge vertical",
+        shortcut(Modifiers::NONE, Key::V),
+    ),
+    (
+        Action::MakeHorizontal,
+        "make-horizontal",
+        "Make edge horizontal",
+        shortcut(Modifiers::NONE, Key::H),
+    ),
+    (
+        Action::MakeDiagonalUp,
+        "make-diagonal-up",
+        "Make edge diagonal up",
+        shortcut(Modifiers::NONE, Key::Slash),
+    ),
+    (
+        Action::MakeDiagonalDown,
+        "make-diagonal-down",
+        "Make edge diagonal down",
+        shortcut(Modifiers::NONE, Key::Backslash),
+    ),
+    (
+        Action::FixLength,
+        "fix-length",
+        "Fix edge length",
+        shortcut(Modifiers::NONE, Key::L),
+    ),
+    (
+        Action::ToBezier,
+        "to-bezier",
+        "To Bézier segment",
+        shortcut(Modifiers::NONE, Key::B),
+    ),
+    (
+        Action::ToArc,
+        "to-arc",
+        "To circular arc",
+        shortcut(Modifiers::NONE, Key::A),
+    ),
+    (
+        Action::Subdivide,
+        "subdivide",
+        "Subdivide edge",
+        shortcut(Modifiers::NONE, Key::S),
+    ),
+    (
+        Action::RemoveConstraint,
+        "remove-constraint",
+        "Remove edge constraint",
+        shortcut(Modifiers::NONE, Key::X),
+    ),
+    (
+        Action::SplitCurve,
+        "split-curve",
+        "Split curve at the pointer",
+        shortcut(Modifiers::SHIFT, Key::S),
+    ),
+    (
+        Action::RemoveBezier,
+        "remove-bezier",
+        "Remove Bézier segment",
+        shortcut(Modifiers::SHIFT, Key::B),
+    ),
+    (
+        Action::SetRadius,
+        "set-radius",
+        "Set arc radius",
+        shortcut(Modifiers::SHIFT, Key::R),
+    ),
+    (
+        Action::ResetRadius,
+        "reset-radius",
+        "Reset arc radius",
+        shortcut(Modifiers::NONE, Key::R),
+    ),
+    (
+        Action::RemoveArc,
+        "remove-arc",
+        "Remove arc",
+        shortcut(Modifiers::SHIFT, Key::A),
+    ),
+    (
+        Action::ToggleLengthDimension,
+        "length-dimension",
+        "Toggle length dimension",
+        shortcut(Modifiers::NONE, Key::D),
+    ),
+    (
+        Action::ToggleAngleDimension,
+        "angle-dimension",
+        "Toggle angle dimension",
+        shortcut(Modifiers::SHIFT, Key::D),
+    ),
+    (
+        Action::ToggleRadiusDimension,
+        "radius-dimension",
+        "Toggle radius dimension",
+        shortcut(Modifiers::ALT, Key::D),
+    ),
+    (
+        Action::NudgeLeft,
+        "nudge-left",
+        "Nudge left (more with [Shift])",
+        shortcut(Modifiers::NONE, Key::ArrowLeft),
+    ),
+    (
+        Action::NudgeRight,
+        "nudge-right",
+        "Nudge right (more with [Shift])",
+        shortcut(Modifiers::NONE, Key::ArrowRight),
+    ),
+    (
+        Action::NudgeUp,
+        "nudge-up",
+        "Nudge up (more with [Shift])",
+        shortcut(Modifiers::NONE, Key::ArrowUp),
+    ),
+    (
+        Action::NudgeDown,
+        "nudge-down",
+        "Nudge down (more with [Shift])",
+        shortcut(Modifiers::NONE, Key::ArrowDown),
+    ),
+    (
+        Action::Deselect,
+        "deselect",
+        "Close the context menu",
+        shortcut(Modifiers::NONE, Key::Escape),
+    ),
+    (
+        Action::NewShape,
+        "new-shape",
+        "New shape",
+        shortcut(Modifiers::CTRL, Key::N),
+    ),
+    (
+        Action::DeleteShape,
+        "delete-shape",
+        "Delete shape",
+        shortcut(Modifiers::CTRL, Key::Delete),
+    ),
+    (
+        Action::Reset,
+        "reset",
+        "Reset (delete all shapes)",
+        shortcut(Modifiers::CTRL, Key::R),
+    ),
+    (
+        Action::ToggleHelp,
+        "help",
+        "Show or hide this list",
+        shortcut(Modifiers::NONE, Key::F1),
+    ),
+];
+
+impl Action {
+    pub fn name(self) -> &'static str {
+        ACTIONS
+            .iter()
+            .find(|(action, ..)| *action == self)
+            .map_or("", |a| a.1)
+    }
+
+    pub fn description(self) -> &'static str {
+        ACTIONS
+            .iter()
+            .find(|(action, ..)| *action == self)
+            .map_or("", |a| a.2)
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        ACTIONS.iter().find(|a| a.1 == name).map(|a| a.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeymapError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+// modifiers and a key joined by `+`, like `Ctrl+Shift+D`
+fn parse_shortcut(text: &str) -> Option<KeyboardShortcut> {
+    // the key comes last and can be `+` itself
+    let (parts, key) = match text.rsplit_once('+') {
+        Some((rest, "")) => (rest.strip_suffix('+').unwrap_or(rest), "+"),
+        Some((rest, key)) => (rest, key),
+        None => ("", text),
+    };
+    let key = Key::from_name(key)?;
+    let mut modifiers = Modifiers::NONE;
+    for part in parts.split('+').filter(|part| !part.is_empty()) {
+        modifiers |= match part.to_lowercase().as_str() {
+            "ctrl" => Modifiers::CTRL,
+            "shift" => Modifiers::SHIFT,
+            "alt" => Modifiers::ALT,
+            "cmd" => Modifiers::COMMAND,
+            _ => return None,
+        };
+    }
+
+    Some(KeyboardShortcut::new(modifiers, key))
+}
+
+// which shortcut triggers which action, read from a plain text file like this one:
+//
+// # one action per line followed by its shortcut, or `none` to leave it unbound
+// to-arc Ctrl+A
+// remove-vertex Backspace
+// subdivide none
+//
+// actions that aren't listed keep their default shortcuts
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    bindings: Vec<(Action, Option<KeyboardShortcut>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: ACTIONS
+                .iter()
+                .map(|&(action, _, _, shortcut)| (action, shortcut))
+                .collect(),
+        }
+    }
+}
+
+impl Keymap {
+    // $POLYA_KEYMAP if set, otherwise polya/keymap in the user's config directory
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = env::var_os("POLYA_KEYMAP") {
+            return Some(path.into());
+        }
+        let config_dir = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+        Some(config_dir.join("polya").join("keymap"))
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, KeymapError> {
+        let mut keymap = Self::default();
+        // the line each binding was set on, 0 for the defaults
+        let mut lines = vec![0; keymap.bindings.len()];
+        for (line_i, line) in text.lines().enumerate() {
+            let error = |message: String| KeymapError {
+                line: line_i + 1,
+                message,
+            };
+            let line = line.split('#').next().unwrap_or_default();
+            let mut words = line.split_whitespace();
+            let Some(name) = words.next() else {
+                continue;
+            };
+            let action =
+                Action::from_name(name).ok_or_else(|| error(format!("unknown action `{name}`")))?;
+            let shortcut = match words.next() {
+                Some("none") => None,
+                Some(text) => Some(
+                    parse_shortcut(text)
+                        .ok_or_else(|| error(format!("invalid shortcut `{text}`")))?,
+                ),
+                None => return Err(error(format!("no shortcut given for `{name}`"))),
+            };
+            if let Some(other) = words.next() {
+                return Err(error(format!("unexpected `{other}`")));
+            }
+            for (binding, binding_line) in keymap.bindings.iter_mut().zip(lines.iter_mut()) {
+                if binding.0 == action {
+                    binding.1 = shortcut;
+                    *binding_line = line_i + 1;
+                }
+            }
+        }
+        // one shortcut can't trigger two actions, the later line of the two is blamed
+        for (i, &(action, shortcut)) in keymap.bindings.iter().enumerate() {
+            let Some(shortcut) = shortcut else {
+                continue;
+            };
+            if let Some(j) =
+                (i + 1..keymap.bindings.len()).find(|&j| keymap.bindings[j].1 == Some(shortcut))
+            {
+                let other = keymap.bindings[j].0;
+                let (action, other) = if lines[i] >= lines[j] {
+                    (action, other)
+                } else {
+                    (other, action)
+                };
+                return Err(KeymapError {
+                    line: lines[i].max(lines[j]),
+                    message: format!(
+                        "`{}` is bound to both `{}` and `{}`",
+                        shortcut.format(&egui::ModifierNames::NAMES, false),
+                        action.name(),
+                        other.name()
+                    ),
+                });
+            }
+        }
+
+        Ok(keymap)
+    }
+
+    // the default keymap if there's no file, also if it can't be read, with a warning
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => Self::from_text(&text).unwrap_or_else(|e| {
+                log::warn!("{}: {e}, using the default keymap", path.display());
+                Self::default()
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                log::warn!("{}: {e}, using the default keymap", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    pub fn shortcut(&self, action: Action) -> Option<KeyboardShortcut> {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .and_then(|(_, shortcut)| *shortcut)
+    }
+
+    pub fn actions(&self) -> impl Iterator<Item = Action> + '_ {
+        self.bindings.iter().map(|(action, _)| *action)
+    }
+
+    // actions whose shortcuts were pressed this frame, with the keys consumed
+    pub fn pressed(&self, ctx: &Context) -> Vec<Action> {
+        // extra modifiers don't prevent a match, so shortcuts with more of them go first
+        let mut bindings: Vec<(Action, KeyboardShortcut)> = self
+            .bindings
+            .iter()
+            .filter_map(|&(action, shortcut)| Some((action, shortcut?)))
+            .collect();
+        bindings.sort_by_key(|(_, shortcut)| {
+            let m = shortcut.modifiers;
+            std::cmp::Reverse(m.alt as u8 + m.ctrl as u8 + m.shift as u8 + m.command as u8)
+        });
+
+        ctx.input_mut(|i| {
+            bindings
+                .into_iter()
+                .filter(|(_, shortcut)| i.consume_shortcut(shortcut))
+                .map(|(action, _)| action)
+                .collect()
+        })
+    }
+}
@@ -1,15 +1,24 @@
 mod app;
-mod calc;
+mod cli;
 mod constants;
 mod dialog;
-mod polygon;
+mod keymap;
 mod render;
 mod state;
-mod vertex;
+
+use std::process::ExitCode;
 
 use crate::app::App;
 
-fn main() {
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args
+        .first()
+        .is_some_and(|command| cli::COMMANDS.contains(&command.as_str()))
+    {
+        return cli::run(&args);
+    }
+
     simple_logging::log_to_stderr(log::LevelFilter::Warn);
     let program_name = format!("{} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
     let native_options = eframe::NativeOptions::default();
@@ -18,4 +27,6 @@ fn main() {
         native_options,
         Box::new(|_| Ok(Box::new(App::default()))),
     );
+
+    ExitCode::SUCCESS
 }
@@ -1,10 +1,19 @@
-use egui::{Painter, Pos2, Rect, Shape, Stroke, Vec2};
-
-use crate::{
-    calc, constants,
+use egui::{Color32, Painter, Shape, Stroke};
+use polya_core::{
+    calc,
+    dimension::Annotation,
+    hull::Convexity,
+    math::{Pos2, Rect, Vec2},
+    polygon::Polygon,
+    simplify,
+    transform::{self, Handle},
+    triangulation::Mesh,
+    units::Units,
     vertex::{EdgeConstraint, Vertex, VertexConstraint},
 };
 
+use crate::constants;
+
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub enum LineAlgorithm {
     #[default]
@@ -12,6 +21,11 @@ pub enum LineAlgorithm {
     Bresenham,
 }
 
+// model points as they are drawn on the screen
+fn to_screen(points: &[Pos2]) -> Vec<egui::Pos2> {
+    points.iter().map(|&p| p.into()).collect()
+}
+
 trait Bresenham {
     fn bresenham_line_segment(&self, points: [Pos2; 2], stroke: Stroke);
 }
@@ -22,7 +36,7 @@ impl Bresenham for Painter {
             // drawing 1x1 rects is the closest we can get to manipulating
             // single pixels in egui
             self.rect_filled(
-                Rect::from_min_max(p, p + Vec2::splat(stroke.width)),
+                Rect::from_min_max(p, p + Vec2::splat(stroke.width.into())).into(),
                 0.0,
                 stroke.color,
             );
@@ -41,7 +55,7 @@ fn render_line_segment(
             painter.bresenham_line_segment(points, stroke);
         }
         LineAlgorithm::Default => {
-            painter.line_segment(points, stroke);
+            painter.line_segment(points.map(Into::into), stroke);
         }
     }
 }
@@ -51,15 +65,22 @@ fn render_cubic_bezier(
     endpoints: [Pos2; 2],
     control: [Pos2; 2],
     stroke: Stroke,
+    tolerance: f64,
 ) {
-    let bezier_points =
-        calc::cubic_bezier_points(endpoints[0], endpoints[1], control[0], control[1]);
+    let bezier_points = calc::cubic_bezier_points(
+        endpoints[0],
+        endpoints[1],
+        control[0],
+        control[1],
+        tolerance,
+    );
     for pair in bezier_points.windows(2) {
-        painter.line_segment([pair[0], pair[1]], stroke);
+        painter.line_segment([pair[0].into(), pair[1].into()], stroke);
     }
 }
 
 // calculates (center, radius) based on v0 and v1's constraints and draws the corresponding arc
+// arcs with both ends at G0 also get a handle at the midpoint for changing their radius
 fn render_circular_arc(
     painter: &Painter,
     v0: Vertex,
@@ -67,11 +88,23 @@ fn render_circular_arc(
     prev: Vertex,
     next: Vertex,
     stroke: Stroke,
+    tolerance: f64,
 ) {
-    let (s, r) = calc::circular_arc_data(v0, v1, prev, next);
-    let arc_points = calc::arc_points(v0.p, v1.p, s, r);
+    let arc = calc::circular_arc_data(v0, v1, prev, next);
+    let arc_points = calc::arc_points(arc, tolerance);
     for pair in arc_points.windows(2) {
-        painter.line_segment([pair[0], pair[1]], stroke);
+        painter.line_segment([pair[0].into(), pair[1].into()], stroke);
+    }
+    if matches!(
+        (v0.vertex_c, v1.vertex_c),
+        (VertexConstraint::G0, VertexConstraint::G0)
+    ) {
+        painter.rect_filled(
+            Rect::from_center_size(arc.midpoint(), Vec2::splat(constants::SIZE_CONTROL_VERTEX))
+                .into(),
+            0.0,
+            constants::COLOR_VERTEX_TER,
+        );
     }
 }
 
@@ -93,6 +126,8 @@ pub fn render_polygon_edges(
     vertices: &[Vertex],
     selected_edge_i: Option<usize>,
     line_algo: LineAlgorithm,
+    tolerance: f64,
+    units: Units,
 ) {
     for i in 0..vertices.len() {
         let color = match selected_edge_i {
@@ -108,27 +143,28 @@ pub fn render_polygon_edges(
                 [v0.p, v1.p],
                 [bezier.control[0], bezier.control[1]],
                 stroke,
+                tolerance,
             );
             painter.add(Shape::dashed_line(
-                &[v0.p, v1.p],
+                &to_screen(&[v0.p, v1.p]),
                 stroke,
                 constants::SIZE_DASHES,
                 constants::SIZE_GAPS,
             ));
             painter.add(Shape::dashed_line(
-                &[v0.p, bezier.control[0]],
+                &to_screen(&[v0.p, bezier.control[0]]),
                 stroke,
                 constants::SIZE_DASHES,
                 constants::SIZE_GAPS,
             ));
             painter.add(Shape::dashed_line(
-                &[bezier.control[0], bezier.control[1]],
+                &to_screen(&bezier.control),
                 stroke,
                 constants::SIZE_DASHES,
                 constants::SIZE_GAPS,
             ));
             painter.add(Shape::dashed_line(
-                &[bezier.control[1], v1.p],
+                &to_screen(&[bezier.control[1], v1.p]),
                 stroke,
                 constants::SIZE_DASHES,
                 constants::SIZE_GAPS,
@@ -138,7 +174,8 @@ pub fn render_polygon_edges(
                     Rect::from_center_size(
                         bezier.control[i],
                         Vec2::splat(constants::SIZE_CONTROL_VERTEX),
-                    ),
+                    )
+                    .into(),
                     0.0,
                     constants::COLOR_VERTEX_TER,
                 );
@@ -146,18 +183,21 @@ pub fn render_polygon_edges(
         } else if vertices[i].arc.is_some() {
             let prev = vertices[(i + vertices.len() - 1) % vertices.len()];
             let next = vertices[(i + 1) % vertices.len()];
-            render_circular_arc(painter, v0, v1, prev, next, stroke);
+            render_circular_arc(painter, v0, v1, prev, next, stroke, tolerance);
         } else {
             let label = match v0.edge_c {
                 Some(EdgeConstraint::Vertical) => "||".to_string(),
+                Some(EdgeConstraint::Horizontal) => "=".to_string(),
                 Some(EdgeConstraint::DiagonalUp) => "/".to_string(),
                 Some(EdgeConstraint::DiagonalDown) => "\\".to_string(),
-                Some(EdgeConstraint::FixedLength(len)) => (len.round()).to_string(),
+                // the length dimension already shows it
+                Some(EdgeConstraint::FixedLength(_)) if v0.dims.length => String::new(),
+                Some(EdgeConstraint::FixedLength(len)) => units.format(len),
                 _ => String::new(),
             };
             render_line_segment(painter, [v0.p, v1.p], stroke, line_algo);
             painter.text(
-                calc::midpoint(v0.p, v1.p),
+                calc::midpoint(v0.p, v1.p).into(),
                 egui::Align2::CENTER_CENTER,
                 label,
                 egui::FontId::proportional(constants::SIZE_LABEL_FONT),
@@ -178,7 +218,7 @@ pub fn render_vertices(
             Some(s_i) if s_i == i => constants::COLOR_VERTEX_SEC,
             _ => constants::COLOR_VERTEX_PRI,
         };
-        painter.circle_filled(v.p, constants::SIZE_VERTEX, color);
+        painter.circle_filled(v.p.into(), constants::SIZE_VERTEX as f32, color);
         let label = if v.bezier.is_some()
             || vertices[(i + vertices.len() - 1) % vertices.len()]
                 .bezier
@@ -192,13 +232,15 @@ pub fn render_vertices(
                 VertexConstraint::G0 => "G0".to_string(),
                 VertexConstraint::G1 => "G1".to_string(),
                 VertexConstraint::C1 => "C1".to_string(),
+                VertexConstraint::G2 => "G2".to_string(),
+                VertexConstraint::C2 => "C2".to_string(),
             }
         } else {
             "".to_string()
         };
         if labels {
             painter.text(
-                v.p + Vec2::splat(constants::SIZE_LABEL_OFFSET),
+                (v.p + Vec2::splat(constants::SIZE_LABEL_OFFSET.into())).into(),
                 egui::Align2::CENTER_CENTER,
                 label,
                 egui::FontId::proportional(constants::SIZE_LABEL_FONT),
@@ -207,3 +249,222 @@ pub fn render_vertices(
         }
     }
 }
+
+// draws spikes normal to the curve with length proportional to its curvature,
+// pointing away from the center of curvature, and the line connecting their tips
+fn render_comb(painter: &Painter, spikes: &[(Pos2, Pos2)]) {
+    let stroke = Stroke::new(constants::SIZE_STROKE, constants::COLOR_COMB);
+    for &(p, tip) in spikes {
+        painter.line_segment([p.into(), tip.into()], stroke);
+    }
+    for pair in spikes.windows(2) {
+        painter.line_segment([pair[0].1.into(), pair[1].1.into()], stroke);
+    }
+}
+
+pub fn render_curvature_combs(painter: &Painter, vertices: &[Vertex], scale: f64) {
+    let n = constants::COMB_SAMPLES;
+    for i in 0..vertices.len() {
+        let next_i = (i + 1) % vertices.len();
+        let (v0, v1) = (vertices[i], vertices[next_i]);
+        let spikes: Vec<_> = if let Some(bezier) = v0.bezier {
+            (0..=n)
+                .map(|k| {
+                    let t = k as f64 / n as f64;
+                    let [c0, c1] = bezier.control;
+                    let p = calc::cubic_bezier_point(v0.p, c0, c1, v1.p, t);
+                    let (tangent, k) = calc::cubic_bezier_curvature(v0.p, c0, c1, v1.p, t);
+                    (p, p + scale * k * tangent.rot90())
+                })
+                .collect()
+        } else if v0.arc.is_some() {
+            let prev = vertices[(i + vertices.len() - 1) % vertices.len()];
+            let next = vertices[(next_i + 1) % vertices.len()];
+            let arc = calc::circular_arc_data(v0, v1, prev, next);
+            (0..=n)
+                .map(|k| {
+                    let p = arc.point_at(arc.start_angle + arc.sweep * k as f64 / n as f64);
+                    (p, p + (scale / arc.radius) * (p - arc.center).normalized())
+                })
+                .collect()
+        } else {
+            continue;
+        };
+        render_comb(painter, &spikes);
+    }
+}
+
+pub fn render_intersections(painter: &Painter, points: &[Pos2]) {
+    for &p in points {
+        painter.circle_stroke(
+            p.into(),
+            constants::SIZE_INTERSECTION_MARKER,
+            Stroke::new(constants::SIZE_STROKE, constants::COLOR_INTERSECTION),
+        );
+    }
+}
+
+// shapes other than the edited one are drawn as plain outlines, holes with dashed lines
+pub fn render_shape(painter: &Painter, polygon: &Polygon, color: Color32, tolerance: f64) {
+    let stroke = Stroke::new(constants::SIZE_STROKE, color);
+    let mut points = to_screen(&polygon.outline_points(tolerance));
+    if let Some(&first) = points.first() {
+        points.push(first);
+    }
+    if polygon.hole {
+        painter.add(Shape::dashed_line(
+            &points,
+            stroke,
+            constants::SIZE_DASHES,
+            constants::SIZE_GAPS,
+        ));
+    } else {
+        painter.add(Shape::line(points, stroke));
+    }
+}
+
+// the bounding box of the edited shape with handles for rotating and scaling it
+pub fn render_transform_handles(painter: &Painter, bbox: Rect, pivot: Pos2) {
+    let stroke = Stroke::new(constants::SIZE_STROKE, constants::COLOR_TRANSFORM);
+    let corners = [
+        bbox.left_top(),
+        bbox.right_top(),
+        bbox.right_bottom(),
+        bbox.left_bottom(),
+        bbox.left_top(),
+    ];
+    painter.add(Shape::dashed_line(
+        &to_screen(&corners),
+        stroke,
+        constants::SIZE_DASHES,
+        constants::SIZE_GAPS,
+    ));
+    for (handle, p) in transform::handles(bbox, pivot) {
+        match handle {
+            Handle::Pivot => {
+                let d = constants::SIZE_CONTROL_VERTEX;
+                painter.circle_stroke(p.into(), d as f32, stroke);
+                painter.line_segment(
+                    [p - Vec2::new(d, 0.0), p + Vec2::new(d, 0.0)].map(Into::into),
+                    stroke,
+                );
+                painter.line_segment(
+                    [p - Vec2::new(0.0, d), p + Vec2::new(0.0, d)].map(Into::into),
+                    stroke,
+                );
+            }
+            Handle::Rotate => {
+                painter.line_segment([bbox.center_top().into(), p.into()], stroke);
+                painter.circle_filled(
+                    p.into(),
+                    constants::SIZE_TRANSFORM_HANDLE,
+                    constants::COLOR_TRANSFORM,
+                );
+            }
+            Handle::Scale | Handle::ScaleX | Handle::ScaleY => {
+                painter.rect_filled(
+                    Rect::from_center_size(
+                        p,
+                        Vec2::splat(2.0 * f64::from(constants::SIZE_TRANSFORM_HANDLE)),
+                    )
+                    .into(),
+                    0.0,
+                    constants::COLOR_TRANSFORM,
+                );
+            }
+        }
+    }
+}
+
+pub fn render_mesh(painter: &Painter, mesh: &Mesh) {
+    let stroke = Stroke::new(constants::SIZE_STROKE, constants::COLOR_MESH);
+    for &[a, b, c] in mesh.triangles.iter() {
+        let (pa, pb, pc) = (mesh.vertices[a], mesh.vertices[b], mesh.vertices[c]);
+        painter.add(Shape::closed_line(to_screen(&[pa, pb, pc]), stroke));
+    }
+}
+
+// the convex hull with the reflex vertices of the outline circled
+pub fn render_convexity(
+    painter: &Painter,
+    convexity: &Convexity,
+    vertices: &[Vertex],
+    tolerance: f64,
+) {
+    let stroke = Stroke::new(constants::SIZE_STROKE, constants::COLOR_HULL);
+    for segment in convexity.hull.iter() {
+        painter.add(Shape::line(to_screen(&segment.points(tolerance)), stroke));
+    }
+    for &v_i in convexity.reflex_vertices.iter() {
+        painter.circle_stroke(
+            vertices[v_i].p.into(),
+            constants::SIZE_INTERSECTION_MARKER,
+            stroke,
+        );
+    }
+}
+
+// the outline left after simplification, with the vertices that would be removed circled
+pub fn render_simplification(painter: &Painter, polygon: &Polygon, kept: &[usize], tolerance: f64) {
+    let stroke = Stroke::new(constants::SIZE_STROKE, constants::COLOR_SIMPLIFIED);
+    let mut points = to_screen(&simplify::simplified(polygon, kept).outline_points(tolerance));
+    if let Some(&first) = points.first() {
+        points.push(first);
+    }
+    painter.add(Shape::dashed_line(
+        &points,
+        stroke,
+        constants::SIZE_DASHES,
+        constants::SIZE_GAPS,
+    ));
+    for (v_i, v) in polygon.vertices.iter().enumerate() {
+        if kept.binary_search(&v_i).is_err() {
+            painter.circle_stroke(v.p.into(), constants::SIZE_VERTEX as f32, stroke);
+        }
+    }
+}
+
+// the chain of edges picked for fitting curves, all the way around if its ends are the same
+pub fn render_chain(
+    painter: &Painter,
+    polygon: &Polygon,
+    start_i: usize,
+    end_i: usize,
+    tolerance: f64,
+) {
+    let n = polygon.vertices.len();
+    let edge_count = match (end_i + n - start_i) % n {
+        0 => n,
+        count => count,
+    };
+    let stroke = Stroke::new(constants::SIZE_CHAIN_STROKE, constants::COLOR_CHAIN);
+    for k in 0..edge_count {
+        painter.add(Shape::line(
+            to_screen(&polygon.edge_points((start_i + k) % n, tolerance)),
+            stroke,
+        ));
+    }
+}
+
+pub fn render_annotations(painter: &Painter, annotations: &[Annotation]) {
+    let stroke = Stroke::new(constants::SIZE_STROKE, constants::COLOR_DIMENSION);
+    for annotation in annotations {
+        for line in annotation.lines.iter() {
+            painter.add(Shape::line(to_screen(line), stroke));
+        }
+        for arrow in annotation.arrows.iter() {
+            painter.add(Shape::convex_polygon(
+                to_screen(arrow),
+                constants::COLOR_DIMENSION,
+                Stroke::NONE,
+            ));
+        }
+        painter.text(
+            annotation.text_pos.into(),
+            egui::Align2::CENTER_CENTER,
+            &annotation.text,
+            egui::FontId::proportional(constants::SIZE_DIMENSION_FONT as f32),
+            constants::COLOR_DIMENSION,
+        );
+    }
+}
@@ -1,27 +1,101 @@
-use egui::{Button, Color32, Context, Modifiers, PointerButton, Rect, Vec2};
-
-use crate::{
-    calc, constants,
-    dialog::FixedLengthDialog,
+use egui::{Button, Color32, Context, Modifiers, PointerButton};
+use polya_core::{
+    boolean::{self, BooleanOp, Contour},
+    calc,
+    hull::Convexity,
+    math::{Pos2, Rect, Vec2},
+    offset::{self, JoinStyle},
     polygon::Polygon,
+    primitives::Primitive,
+    properties::Properties,
+    simplify::{self, SimplifyAlgorithm},
+    transform::{self, Handle, Transform},
+    triangulation::{self, Mesh, TriangulationError},
+    units::Units,
     vertex::{CircleArc, EdgeConstraint, Vertex, VertexConstraint},
 };
 
+use crate::{
+    constants,
+    dialog::{ArcDialog, CornerDialog, FixedLengthDialog},
+    keymap::{Action, Keymap},
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CreationMode {
+    // a vertex for every click
+    #[default]
+    Click,
+    // a stroke drawn while holding LMB
+    Freehand,
+    // shapes dragged out with LMB
+    Rectangle,
+    RegularPolygon,
+    Circle,
+    RoundedRectangle,
+}
+
+// what happens to a freehand stroke once it's closed
+#[derive(Clone, Copy, Debug)]
+pub struct SketchOptions {
+    pub simplify_tolerance: f64,
+    // None if the stroke should stay a polygon with straight edges
+    pub fit_tolerance: Option<f64>,
+}
+
 #[derive(Debug, Default)]
 pub struct CreatingState {
     pub vertices: Vec<Vertex>,
+    // shapes created earlier
+    pub shapes: Vec<Polygon>,
+    // whether a freehand stroke is being drawn right now
+    pub sketching: bool,
+    // where the drag of a primitive shape started and where it is now
+    pub drag: Option<(Pos2, Pos2)>,
 }
 
 #[derive(Debug)]
 pub struct EditingState {
+    // the shape being edited
     pub polygon: Polygon,
+    // all the other shapes
+    pub shapes: Vec<Polygon>,
+    // the shape in `shapes` used as the second operand of boolean operations
+    pub operand_i: usize,
     pub dragged_vertex_i: Option<usize>,
     // edge index, which control vertex of this edge (0/1)
     pub dragged_control_vertex_i: Option<(usize, usize)>,
+    // edge index of the arc whose midpoint is being dragged
+    pub dragged_arc_handle_i: Option<usize>,
     pub drag_anchor_i: Option<usize>,
     pub selected_vertex_i: Option<usize>,
     pub selected_edge_i: Option<usize>,
+    // where the selecting click happened
+    pub select_pos: Option<Pos2>,
     pub fixed_length_dialog: FixedLengthDialog,
+    pub arc_dialog: ArcDialog,
+    pub fillet_dialog: CornerDialog,
+    pub chamfer_dialog: CornerDialog,
+    // the dialog to open the next time the context menu is shown, asked for by a shortcut
+    pub pending_dialog: Option<Action>,
+    // where the pivot of rotations and scaling is relative to the centroid
+    pub pivot_offset: Vec2,
+    pub transform_drag: Option<Box<TransformDrag>>,
+    // ends of the chain of edges to fit Bézier curves to
+    pub chain_start_i: Option<usize>,
+    pub chain_end_i: Option<usize>,
+    // what the last edit did besides what was asked for, shown until the next click
+    pub notice: Option<String>,
+}
+
+// a transform handle being dragged, the transform is always
+// applied to the shape as it was when the drag started
+#[derive(Debug)]
+pub struct TransformDrag {
+    pub handle: Handle,
+    pub start_pos: Pos2,
+    pub pivot: Pos2,
+    pub vertices: Vec<Vertex>,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -34,12 +108,21 @@ impl CreatingState {
         Self::default()
     }
 
+    pub fn with_shapes(shapes: Vec<Polygon>) -> Self {
+        Self {
+            vertices: Vec::new(),
+            shapes,
+            sketching: false,
+            drag: None,
+        }
+    }
+
     pub fn handle_add_point(
         &mut self,
         ctx: &Context,
         canvas_rect: Rect,
     ) -> Option<StateTransition> {
-        if let Some(mouse_pos) = ctx.pointer_interact_pos()
+        if let Some(mouse_pos) = ctx.pointer_interact_pos().map(Pos2::from)
             && canvas_rect.contains(mouse_pos)
             && ctx.input(|i| i.pointer.button_released(PointerButton::Primary))
         {
@@ -51,18 +134,115 @@ impl CreatingState {
 
         None
     }
+
+    // samples the stroke while LMB is held, releasing it near the start closes the shape;
+    // a stroke released elsewhere can be continued by drawing again
+    pub fn handle_sketch(
+        &mut self,
+        ctx: &Context,
+        canvas_rect: Rect,
+        options: SketchOptions,
+    ) -> Option<StateTransition> {
+        let mouse_pos = ctx.pointer_interact_pos().map(Pos2::from)?;
+        let (down, released) = ctx.input(|i| {
+            (
+                i.pointer.button_down(PointerButton::Primary),
+                i.pointer.button_released(PointerButton::Primary),
+            )
+        });
+        if down && (self.sketching || canvas_rect.contains(mouse_pos)) {
+            self.sketching = true;
+            if self
+                .vertices
+                .last()
+                .is_none_or(|v| v.p.distance(mouse_pos) >= constants::SKETCH_SAMPLE_DIST)
+            {
+                self.vertices.push(Vertex::new(mouse_pos));
+            }
+        } else if released && self.sketching {
+            self.sketching = false;
+            let start = self.vertices.first()?.p;
+            let is_closing = |v: &Vertex| v.p.distance(start) < constants::SKETCH_CLOSE_DIST;
+            if self.vertices.len() > 3 && self.vertices.last().is_some_and(is_closing) {
+                // the end of the stroke overlaps the start
+                while self.vertices.len() > 3 && self.vertices.last().is_some_and(is_closing) {
+                    self.vertices.pop();
+                }
+                self.finish_sketch(options);
+                return Some(StateTransition::ToEditing);
+            }
+        }
+
+        None
+    }
+
+    // the primitive shape is dragged out with LMB and finished when it's released
+    pub fn handle_drag_primitive(
+        &mut self,
+        ctx: &Context,
+        canvas_rect: Rect,
+        primitive: Primitive,
+    ) -> Option<StateTransition> {
+        let mouse_pos = ctx.pointer_interact_pos().map(Pos2::from)?;
+        let (down, released) = ctx.input(|i| {
+            (
+                i.pointer.button_down(PointerButton::Primary),
+                i.pointer.button_released(PointerButton::Primary),
+            )
+        });
+        if down {
+            match &mut self.drag {
+                Some((_, end)) => *end = mouse_pos,
+                None if canvas_rect.contains(mouse_pos) => self.drag = Some((mouse_pos, mouse_pos)),
+                None => (),
+            }
+        } else if released && let Some((start, _)) = self.drag.take() {
+            let polygon = primitive.build(start, mouse_pos)?;
+            self.vertices = polygon.vertices;
+            return Some(StateTransition::ToEditing);
+        }
+
+        None
+    }
+
+    fn finish_sketch(&mut self, options: SketchOptions) {
+        let polygon = Polygon::new(std::mem::take(&mut self.vertices));
+        let kept = simplify::simplify(
+            &polygon,
+            SimplifyAlgorithm::DouglasPeucker,
+            options.simplify_tolerance,
+        );
+        let mut polygon = simplify::simplified(&polygon, &kept);
+        if let Some(tolerance) = options.fit_tolerance {
+            polygon.try_fit_beziers(0, 0, tolerance);
+        }
+        self.vertices = polygon.vertices;
+    }
 }
 
 impl EditingState {
-    pub fn new(vertices: Vec<Vertex>) -> Self {
+    pub fn new(vertices: Vec<Vertex>, shapes: Vec<Polygon>) -> Self {
         Self {
             polygon: Polygon::new(vertices),
+            shapes,
+            operand_i: 0,
             dragged_vertex_i: None,
             dragged_control_vertex_i: None,
+            dragged_arc_handle_i: None,
             drag_anchor_i: None,
             selected_vertex_i: None,
             selected_edge_i: None,
+            select_pos: None,
             fixed_length_dialog: FixedLengthDialog::default(),
+            arc_dialog: ArcDialog::default(),
+            fillet_dialog: CornerDialog::new(constants::ID_FILLET_DIALOG, "Radius:"),
+            chamfer_dialog: CornerDialog::new(constants::ID_CHAMFER_DIALOG, "Distance:"),
+            pending_dialog: None,
+            pivot_offset: Vec2::ZERO,
+            transform_drag: None,
+            chain_start_i: None,
+            chain_end_i: None,
+            notice: None,
         }
     }
 
@@ -77,7 +257,7 @@ impl EditingState {
             Vertex::from((590.0, 480.0)),
             Vertex::from((400.0, 350.0)),
         ];
-        let mut state = Self::new(vertices);
+        let mut state = Self::new(vertices, Vec::new());
         state.polygon.init_bezier(0);
         state.polygon.make_arc(2);
         state.polygon.vertices[1].edge_c = Some(EdgeConstraint::DiagonalUp);
@@ -88,8 +268,222 @@ impl EditingState {
         state
     }
 
+    // all shapes, with the edited one first
+    pub fn into_shapes(self) -> Vec<Polygon> {
+        std::iter::once(self.polygon).chain(self.shapes).collect()
+    }
+
+    fn activate(&mut self, shape_i: usize) {
+        std::mem::swap(&mut self.polygon, &mut self.shapes[shape_i]);
+        self.selected_vertex_i = None;
+        self.selected_edge_i = None;
+        self.clear_chain();
+        self.pivot_offset = Vec2::ZERO;
+    }
+
+    // removes the edited shape and starts editing another one
+    // returns false if there are no shapes left
+    pub fn delete_polygon(&mut self) -> bool {
+        let Some(polygon) = self.shapes.pop() else {
+            return false;
+        };
+        self.polygon = polygon;
+        self.selected_vertex_i = None;
+        self.selected_edge_i = None;
+        self.clear_chain();
+        self.pivot_offset = Vec2::ZERO;
+        self.operand_i = self.operand_i.min(self.shapes.len().saturating_sub(1));
+
+        true
+    }
+
+    // clicking on another shape starts editing it
+    pub fn handle_activate(&mut self, ctx: &Context, tolerance: f64) {
+        if let Some(mouse_pos) = ctx.pointer_interact_pos().map(Pos2::from)
+            && ctx.input(|i| i.pointer.button_pressed(PointerButton::Primary))
+            && self.transform_drag.is_none()
+            && !self.polygon.is_near(mouse_pos, tolerance)
+            && let Some(shape_i) = self
+                .shapes
+                .iter()
+                .position(|shape| shape.is_near(mouse_pos, tolerance))
+        {
+            self.activate(shape_i);
+        }
+    }
+
+    // the edited shape (or the operand) together with the holes inside it
+    fn region(&self, polygon: &Polygon, tolerance: f64) -> Vec<Contour> {
+        polygon.region(
+            std::iter::once(&self.polygon).chain(self.shapes.iter()),
+            tolerance,
+        )
+    }
+
+    // the edited shape becomes its convex hull
+    pub fn replace_with_hull(&mut self, tolerance: f64) {
+        let hull = Convexity::of(&self.polygon, tolerance).hull;
+        if hull.len() < 3 {
+            return;
+        }
+        self.polygon = Polygon {
+            reject_self_intersections: self.polygon.reject_self_intersections,
+            ..Polygon::from_segments(&hull, self.polygon.hole)
+        };
+        self.selected_vertex_i = None;
+        self.selected_edge_i = None;
+        self.clear_chain();
+    }
+
+    // removes the vertices given by the simplification of the edited shape
+    pub fn apply_simplification(&mut self, algo: SimplifyAlgorithm, tolerance: f64) {
+        let kept = simplify::simplify(&self.polygon, algo, tolerance);
+        if kept.len() == self.polygon.vertices.len() {
+            return;
+        }
+        // dropping vertices can make the outline cross itself
+        // or leave the constraints of its curves unsatisfiable
+        let mut polygon = simplify::simplified(&self.polygon, &kept);
+        if !polygon.resolve_constraints(0)
+            || (polygon.reject_self_intersections && polygon.is_self_intersecting())
+        {
+            self.notice = Some("The simplified shape would be invalid".to_string());
+            return;
+        }
+        self.polygon = polygon;
+        self.selected_vertex_i = None;
+        self.selected_edge_i = None;
+        self.clear_chain();
+    }
+
+    // the ends of the chain are vertex indices, so they're dropped
+    // whenever vertices are inserted or removed
+    fn clear_chain(&mut self) {
+        self.chain_start_i = None;
+        self.chain_end_i = None;
+    }
+
+    // the picked ends of the chain, if both are still vertices of the edited shape
+    pub fn chain(&self) -> Option<(usize, usize)> {
+        let n = self.polygon.vertices.len();
+        match (self.chain_start_i, self.chain_end_i) {
+            (Some(start_i), Some(end_i)) if start_i < n && end_i < n => Some((start_i, end_i)),
+            _ => None,
+        }
+    }
+
+    pub fn fit_beziers(&mut self, tolerance: f64) {
+        let Some((start_i, end_i)) = self.chain() else {
+            return;
+        };
+        if self.polygon.try_fit_beziers(start_i, end_i, tolerance) {
+            self.selected_vertex_i = None;
+            self.selected_edge_i = None;
+            self.clear_chain();
+        }
+    }
+
+    // triangulation of the edited shape with the holes inside it
+    pub fn triangulate(&self, delaunay: bool, tolerance: f64) -> Result<Mesh, TriangulationError> {
+        let region = self.region(&self.polygon, tolerance);
+        triangulation::triangulate(&region[0], &region[1..], delaunay)
+    }
+
+    // combines the edited shape with the operand, the results are added
+    // as new shapes and the first of them becomes the edited one
+    pub fn apply_boolean(&mut self, op: BooleanOp, tolerance: f64) {
+        let Some(operand) = self.shapes.get(self.operand_i) else {
+            return;
+        };
+        let a = self.region(&self.polygon, tolerance);
+        let b = self.region(operand, tolerance);
+        let results = boolean::apply(op, &a, &b);
+        if results.is_empty() {
+            return;
+        }
+        let first_i = self.shapes.len();
+        self.shapes.extend(
+            results
+                .iter()
+                .map(|contour| Polygon::from_points(contour, boolean::signed_area(contour) < 0.0)),
+        );
+        self.activate(first_i);
+    }
+
+    // adds the offsets of the edited shape as new shapes, the first of them becomes the edited one
+    pub fn apply_offset(&mut self, distance: f64, style: JoinStyle, tolerance: f64) {
+        let results = offset::offset(&self.polygon, distance, style, tolerance);
+        if results.is_empty() {
+            return;
+        }
+        let first_i = self.shapes.len();
+        self.shapes.extend(results);
+        self.activate(first_i);
+    }
+
+    pub fn pivot(&self) -> Pos2 {
+        Properties::of(&self.polygon).centroid + self.pivot_offset
+    }
+
+    // the pivot stays where it is, even though the centroid moves
+    pub fn try_transform(&mut self, transform: Transform, release: bool) -> bool {
+        if !self.polygon.try_transform(transform, release) {
+            if !transform.is_uniform() && self.polygon.vertices.iter().any(|v| v.arc.is_some()) {
+                self.notice = Some("Shapes with arcs can only be scaled uniformly".to_string());
+            }
+            return false;
+        }
+        self.pivot_offset = transform.pivot - Properties::of(&self.polygon).centroid;
+
+        true
+    }
+
+    // dragging the handles around the bounding box rotates or scales the edited shape,
+    // with [Shift] rotations snap to multiples of a fixed angle
+    pub fn handle_transform(&mut self, ctx: &Context, release: bool) {
+        let Some(mouse_pos) = ctx.pointer_interact_pos().map(Pos2::from) else {
+            return;
+        };
+        if !ctx.input(|i| i.pointer.button_down(PointerButton::Primary)) {
+            self.transform_drag = None;
+            return;
+        }
+        let Some(drag) = &self.transform_drag else {
+            if ctx.input(|i| i.pointer.button_pressed(PointerButton::Primary)) {
+                let pivot = self.pivot();
+                let bbox = Properties::of(&self.polygon).bounding_box;
+                self.transform_drag =
+                    transform::nearby_handle(bbox, pivot, mouse_pos).map(|handle| {
+                        Box::new(TransformDrag {
+                            handle,
+                            start_pos: mouse_pos,
+                            pivot,
+                            vertices: self.polygon.vertices.clone(),
+                        })
+                    });
+            }
+            return;
+        };
+        if drag.handle == Handle::Pivot {
+            self.pivot_offset = mouse_pos - Properties::of(&self.polygon).centroid;
+            return;
+        }
+        let snap = ctx.input(|i| i.modifiers.shift);
+        if let Some(t) =
+            transform::from_drag(drag.handle, drag.pivot, drag.start_pos, mouse_pos, snap)
+        {
+            let current = std::mem::replace(&mut self.polygon.vertices, drag.vertices.clone());
+            if !self.try_transform(t, release) {
+                self.polygon.vertices = current;
+            }
+        }
+    }
+
     pub fn handle_drag_vertex(&mut self, ctx: &Context) {
-        if let Some(mouse_pos) = ctx.pointer_interact_pos()
+        if self.transform_drag.is_some() {
+            return;
+        }
+        if let Some(mouse_pos) = ctx.pointer_interact_pos().map(Pos2::from)
             && ctx.input(|i| {
                 i.pointer.button_down(PointerButton::Primary)
                     && i.modifiers.matches_exact(Modifiers::NONE)
@@ -104,6 +498,10 @@ impl EditingState {
                 if self.polygon.vertices[v_i].bezier.is_some() {
                     self.polygon.try_move_control_vertex(v_i, which, mouse_pos);
                 }
+            } else if let Some(e_i) = self.dragged_arc_handle_i {
+                if self.polygon.can_set_arc_radius(e_i) {
+                    self.polygon.try_move_arc_handle(e_i, mouse_pos);
+                }
             } else {
                 // start dragging
                 for (i, v) in self.polygon.vertices.iter().enumerate() {
@@ -118,15 +516,22 @@ impl EditingState {
                         break;
                     }
                 }
+                if self.dragged_vertex_i.is_none() && self.dragged_control_vertex_i.is_none() {
+                    self.dragged_arc_handle_i = self.polygon.nearby_arc_handle(mouse_pos);
+                }
             }
         } else {
             self.dragged_vertex_i = None;
             self.dragged_control_vertex_i = None;
+            self.dragged_arc_handle_i = None;
         }
     }
 
     pub fn handle_drag_polygon(&mut self, ctx: &Context) {
-        if let Some(mouse_pos) = ctx.pointer_interact_pos()
+        if self.transform_drag.is_some() {
+            return;
+        }
+        if let Some(mouse_pos) = ctx.pointer_interact_pos().map(Pos2::from)
             && ctx.input(|i| {
                 i.pointer.button_down(PointerButton::Primary)
                     && i.modifiers.matches_exact(Modifiers::SHIFT)
@@ -148,10 +553,43 @@ impl EditingState {
         }
     }
 
-    pub fn handle_select(&mut self, ctx: &Context) {
-        if let Some(mouse_pos) = ctx.pointer_interact_pos()
+    // double-clicking an edge inserts a vertex into it
+    pub fn handle_insert_vertex(&mut self, ctx: &Context, tolerance: f64) {
+        if ctx.input(|i| i.pointer.primary_pressed()) {
+            self.notice = None;
+        }
+        let Some(mouse_pos) = ctx.pointer_interact_pos().map(Pos2::from) else {
+            return;
+        };
+        if !ctx.input(|i| i.pointer.button_double_clicked(PointerButton::Primary))
+            || self.polygon.vertices.iter().any(|v| v.is_near(mouse_pos))
+        {
+            return;
+        }
+        let Some(e_i) = (0..self.polygon.vertices.len())
+            .find(|&e_i| self.polygon.is_near_edge(e_i, mouse_pos, tolerance))
+        else {
+            return;
+        };
+        let fixed_length = matches!(
+            self.polygon.vertices[e_i].edge_c,
+            Some(EdgeConstraint::FixedLength(_))
+        );
+        if self.polygon.try_insert_vertex(e_i, mouse_pos) {
+            self.selected_vertex_i = None;
+            self.selected_edge_i = None;
+            self.clear_chain();
+            if fixed_length {
+                self.notice = Some("The fixed length of the split edge was removed".to_string());
+            }
+        }
+    }
+
+    pub fn handle_select(&mut self, ctx: &Context, tolerance: f64) {
+        if let Some(mouse_pos) = ctx.pointer_interact_pos().map(Pos2::from)
             && ctx.input(|i| i.pointer.button_down(PointerButton::Secondary))
         {
+            self.select_pos = Some(mouse_pos);
             self.selected_vertex_i = self
                 .polygon
                 .vertices
@@ -166,19 +604,239 @@ impl EditingState {
                 .vertices
                 .iter()
                 .enumerate()
-                .position(|(i, _)| self.polygon.is_near_edge(i, mouse_pos));
+                .position(|(i, _)| self.polygon.is_near_edge(i, mouse_pos, tolerance));
             if self.selected_edge_i.is_some() {
                 self.selected_vertex_i = None;
             }
         }
     }
 
-    pub fn handle_vertex_context_menu(&mut self, ctx: &Context) {
+    // whether the action can be applied to the vertex v_i
+    fn vertex_action_enabled(&self, v_i: usize, action: Action) -> bool {
+        match action {
+            Action::RemoveVertex | Action::StartChain | Action::EndChain | Action::SetG0 => true,
+            Action::SetG1 => self.polygon.can_be_g1(v_i),
+            Action::SetC1 => self.polygon.can_be_c1(v_i),
+            Action::SetG2 => self.polygon.can_be_g2(v_i),
+            Action::SetC2 => self.polygon.can_be_c2(v_i),
+            Action::Fillet | Action::Chamfer => self.polygon.can_cut_corner(v_i),
+            _ => false,
+        }
+    }
+
+    pub fn apply_vertex_action(&mut self, v_i: usize, action: Action) {
+        match action {
+            Action::RemoveVertex => {
+                self.polygon.remove_vertex(v_i);
+                self.clear_chain();
+            }
+            Action::StartChain => self.chain_start_i = Some(v_i),
+            Action::EndChain => self.chain_end_i = Some(v_i),
+            Action::SetG0 => self
+                .polygon
+                .try_set_vertex_constraint(v_i, VertexConstraint::G0),
+            Action::SetG1 | Action::SetC1 => {
+                let vertex_c = if action == Action::SetG1 {
+                    VertexConstraint::G1
+                } else {
+                    VertexConstraint::C1
+                };
+                self.polygon.try_set_vertex_constraint(v_i, vertex_c);
+                self.polygon
+                    .try_move_vertex(v_i, self.polygon.vertices[v_i].p);
+            }
+            Action::SetG2 => self.set_curvature_constraint(v_i, VertexConstraint::G2),
+            Action::SetC2 => self.set_curvature_constraint(v_i, VertexConstraint::C2),
+            // these ask for a value first, in a dialog of the context menu
+            Action::Fillet | Action::Chamfer => {
+                self.selected_vertex_i = Some(v_i);
+                self.pending_dialog = Some(action);
+                return;
+            }
+            _ => return,
+        }
+        self.selected_vertex_i = None;
+    }
+
+    // G2/C2 can be enforced only if one of the joined curves has its other end free,
+    // so the polygon may make one of those ends G0, which the user is told about
+    fn set_curvature_constraint(&mut self, v_i: usize, vertex_c: VertexConstraint) {
+        let n = self.polygon.vertices.len();
+        let ends = [(v_i + n - 1) % n, (v_i + 1) % n];
+        let before = ends.map(|i| self.polygon.vertices[i].vertex_c);
+        self.polygon.try_set_vertex_constraint(v_i, vertex_c);
+        if ends.iter().zip(before).any(|(&i, c)| {
+            c != VertexConstraint::G0 && self.polygon.vertices[i].vertex_c == VertexConstraint::G0
+        }) {
+            self.notice =
+                Some("The joint at the other end of a curve was relaxed to G0".to_string());
+        }
+    }
+
+    // whether the action can be applied to the edge e_i
+    fn edge_action_enabled(&self, e_i: usize, action: Action) -> bool {
+        let v = self.polygon.vertices[e_i];
+        let plain = v.edge_c.is_none() && v.bezier.is_none() && v.arc.is_none();
+        match action {
+            Action::MakeVertical => plain && !self.polygon.has_vertical_neighbor(e_i),
+            Action::MakeHorizontal => plain && !self.polygon.has_horizontal_neighbor(e_i),
+            Action::MakeDiagonalUp
+            | Action::MakeDiagonalDown
+            | Action::ToBezier
+            | Action::ToArc => plain,
+            Action::FixLength => plain,
+            Action::Subdivide => v.bezier.is_none() && v.arc.is_none(),
+            Action::RemoveConstraint => v.edge_c.is_some(),
+            Action::SplitCurve => v.bezier.is_some() && self.select_pos.is_some(),
+            Action::RemoveBezier => v.bezier.is_some(),
+            Action::SetRadius => self.polygon.can_set_arc_radius(e_i),
+            Action::ResetRadius => self.polygon.has_arc_radius(e_i),
+            Action::RemoveArc | Action::ToggleRadiusDimension => v.arc.is_some(),
+            Action::ToggleLengthDimension | Action::ToggleAngleDimension => true,
+            _ => false,
+        }
+    }
+
+    pub fn apply_edge_action(&mut self, e_i: usize, action: Action) {
+        match action {
+            Action::MakeVertical => self
+                .polygon
+                .try_set_edge_constraint(e_i, EdgeConstraint::Vertical),
+            Action::MakeHorizontal => self
+                .polygon
+                .try_set_edge_constraint(e_i, EdgeConstraint::Horizontal),
+            Action::MakeDiagonalUp => self
+                .polygon
+                .try_set_edge_constraint(e_i, EdgeConstraint::DiagonalUp),
+            Action::MakeDiagonalDown => self
+                .polygon
+                .try_set_edge_constraint(e_i, EdgeConstraint::DiagonalDown),
+            Action::ToBezier | Action::ToArc => {
+                if action == Action::ToBezier {
+                    self.polygon.init_bezier(e_i);
+                } else {
+                    self.polygon.make_arc(e_i);
+                }
+                self.polygon.vertices[e_i].edge_c = None;
+                self.polygon
+                    .try_move_vertex(e_i, self.polygon.vertices[e_i].p);
+            }
+            Action::Subdivide => {
+                self.polygon.subdivide_edge(e_i);
+                self.clear_chain();
+            }
+            Action::RemoveConstraint => self.polygon.vertices[e_i].edge_c = None,
+            Action::SplitCurve => {
+                if let Some(select_pos) = self.select_pos {
+                    self.split_bezier_at(e_i, select_pos);
+                    self.clear_chain();
+                }
+            }
+            Action::RemoveBezier => self.polygon.vertices[e_i].bezier = None,
+            Action::ResetRadius => self.polygon.try_set_arc(e_i, CircleArc::default()),
+            Action::RemoveArc => self.polygon.vertices[e_i].arc = None,
+            Action::FixLength | Action::SetRadius => {
+                self.selected_edge_i = Some(e_i);
+                self.pending_dialog = Some(action);
+                return;
+            }
+            // these keep the edge selected, so that more of them can be toggled
+            Action::ToggleLengthDimension => {
+                let dims = &mut self.polygon.vertices[e_i].dims;
+                dims.length = !dims.length;
+                return;
+            }
+            Action::ToggleAngleDimension => {
+                let dims = &mut self.polygon.vertices[e_i].dims;
+                dims.angle = !dims.angle;
+                return;
+            }
+            Action::ToggleRadiusDimension => {
+                let dims = &mut self.polygon.vertices[e_i].dims;
+                dims.radius = !dims.radius;
+                return;
+            }
+            _ => return,
+        }
+        self.selected_edge_i = None;
+    }
+
+    // moves the selected vertex or edge, or the whole polygon if nothing is selected
+    fn nudge(&mut self, v_i: Option<usize>, e_i: Option<usize>, delta: Vec2) {
+        match (v_i, e_i) {
+            (Some(v_i), _) => {
+                self.polygon
+                    .try_move_vertex(v_i, self.polygon.vertices[v_i].p + delta);
+            }
+            (None, Some(e_i)) => {
+                self.polygon.try_move_edge(e_i, delta);
+            }
+            (None, None) => self.polygon.move_polygon(delta),
+        }
+    }
+
+    // applies the actions triggered from the keyboard to the selected vertex or edge,
+    // or to the one under the pointer if nothing is selected
+    pub fn handle_shortcuts(&mut self, ctx: &Context, actions: &[Action], tolerance: f64) {
+        if actions.is_empty() {
+            return;
+        }
+        let (mut v_i, mut e_i) = (self.selected_vertex_i, self.selected_edge_i);
+        if v_i.is_none()
+            && e_i.is_none()
+            && let Some(pointer_pos) = ctx.pointer_hover_pos().map(Pos2::from)
+        {
+            v_i = self
+                .polygon
+                .vertices
+                .iter()
+                .position(|v| v.is_near(pointer_pos));
+            if v_i.is_none() {
+                e_i = (0..self.polygon.vertices.len())
+                    .position(|i| self.polygon.is_near_edge(i, pointer_pos, tolerance));
+                self.select_pos = Some(pointer_pos);
+            }
+        }
+        let step = if ctx.input(|i| i.modifiers.shift) {
+            constants::NUDGE_STEP_FAR
+        } else {
+            constants::NUDGE_STEP
+        };
+        for &action in actions {
+            match action {
+                Action::NudgeLeft => self.nudge(v_i, e_i, Vec2::new(-step, 0.0)),
+                Action::NudgeRight => self.nudge(v_i, e_i, Vec2::new(step, 0.0)),
+                Action::NudgeUp => self.nudge(v_i, e_i, Vec2::new(0.0, -step)),
+                Action::NudgeDown => self.nudge(v_i, e_i, Vec2::new(0.0, step)),
+                Action::Deselect => {
+                    self.selected_vertex_i = None;
+                    self.selected_edge_i = None;
+                }
+                _ => {
+                    if let Some(v_i) = v_i
+                        && self.vertex_action_enabled(v_i, action)
+                    {
+                        self.apply_vertex_action(v_i, action);
+                    } else if let Some(e_i) = e_i
+                        && self.edge_action_enabled(e_i, action)
+                    {
+                        self.apply_edge_action(e_i, action);
+                    } else {
+                        continue;
+                    }
+                    // the indices may be stale after that
+                    return;
+                }
+            }
+        }
+    }
+
+    pub fn handle_vertex_context_menu(&mut self, ctx: &Context, units: Units, keymap: &Keymap) {
         let Some(v_i) = self.selected_vertex_i else {
             return;
         };
         let menu_pos =
-            self.polygon.vertices[v_i].p + Vec2::splat(constants::SIZE_CONTEXT_MENU_OFFSET);
+            self.polygon.vertices[v_i].p + Vec2::splat(constants::SIZE_CONTEXT_MENU_OFFSET.into());
         egui::containers::Area::new(constants::ID_VERTEX_CONTEXT_MENU.into())
             .fixed_pos(menu_pos)
             .show(ctx, |ui| {
@@ -188,65 +846,91 @@ impl EditingState {
                     .fill(Color32::TRANSPARENT)
                     .show(ui, |ui| {
                         ui.set_min_width(constants::SIZE_CONTEXT_MENU);
-                        ui.spacing_mut().item_spacing = Vec2::ZERO;
+                        ui.spacing_mut().item_spacing = egui::Vec2::ZERO;
                         ui.with_layout(egui::Layout::top_down_justified(egui::Align::LEFT), |ui| {
-                            if ui.add(Button::new("Remove")).clicked() {
-                                self.polygon.remove_vertex(v_i);
-                                self.selected_vertex_i = None;
+                            let mut clicked = None;
+                            if action_button(ui, keymap, Action::RemoveVertex, "Remove", true)
+                                .clicked()
+                            {
+                                clicked = Some(Action::RemoveVertex);
+                            }
+                            let can_cut = self.polygon.can_cut_corner(v_i);
+                            let fillet_btn =
+                                action_button(ui, keymap, Action::Fillet, "Fillet", can_cut);
+                            self.fillet_dialog.render(&fillet_btn);
+                            if fillet_btn.clicked() || self.take_pending_dialog(Action::Fillet) {
+                                let max_cut = self.polygon.max_corner_cut(v_i);
+                                self.fillet_dialog.open(
+                                    ui,
+                                    constants::CORNER_RADIUS,
+                                    self.polygon.fillet_radius(v_i, max_cut),
+                                    units,
+                                );
                             }
-                            if ui.add(Button::new("Set G0")).clicked() {
-                                self.polygon
-                                    .try_set_vertex_constraint(v_i, VertexConstraint::G0);
+                            if self.fillet_dialog.applied {
+                                self.polygon.try_fillet(v_i, self.fillet_dialog.value);
                                 self.selected_vertex_i = None;
+                                self.clear_chain();
+                                self.fillet_dialog.applied = false;
                             }
-                            if ui
-                                .add_enabled(self.polygon.can_be_g1(v_i), Button::new("Set G1"))
-                                .clicked()
-                            {
-                                self.polygon
-                                    .try_set_vertex_constraint(v_i, VertexConstraint::G1);
-                                self.polygon
-                                    .try_move_vertex(v_i, self.polygon.vertices[v_i].p);
+                            let chamfer_btn =
+                                action_button(ui, keymap, Action::Chamfer, "Chamfer", can_cut);
+                            self.chamfer_dialog.render(&chamfer_btn);
+                            if chamfer_btn.clicked() || self.take_pending_dialog(Action::Chamfer) {
+                                self.chamfer_dialog.open(
+                                    ui,
+                                    constants::CHAMFER_DISTANCE,
+                                    self.polygon.max_corner_cut(v_i),
+                                    units,
+                                );
+                            }
+                            if self.chamfer_dialog.applied {
+                                self.polygon.try_chamfer(v_i, self.chamfer_dialog.value);
                                 self.selected_vertex_i = None;
+                                self.clear_chain();
+                                self.chamfer_dialog.applied = false;
                             }
-                            if ui
-                                .add_enabled(self.polygon.can_be_c1(v_i), Button::new("Set C1"))
+                            for (action, label) in [
+                                (Action::StartChain, "Start chain here"),
+                                (Action::EndChain, "End chain here"),
+                                (Action::SetG0, "Set G0"),
+                                (Action::SetG1, "Set G1"),
+                                (Action::SetC1, "Set C1"),
+                                (Action::SetG2, "Set G2"),
+                                (Action::SetC2, "Set C2"),
+                            ] {
+                                if action_button(
+                                    ui,
+                                    keymap,
+                                    action,
+                                    label,
+                                    self.vertex_action_enabled(v_i, action),
+                                )
                                 .clicked()
-                            {
-                                self.polygon
-                                    .try_set_vertex_constraint(v_i, VertexConstraint::C1);
-                                self.polygon
-                                    .try_move_vertex(v_i, self.polygon.vertices[v_i].p);
-                                self.selected_vertex_i = None;
+                                {
+                                    clicked = Some(action);
+                                }
+                            }
+                            if let Some(action) = clicked {
+                                self.apply_vertex_action(v_i, action);
                             }
                         });
                     });
             });
     }
 
-    pub fn handle_edge_context_menu(&mut self, ctx: &Context) {
+    pub fn handle_edge_context_menu(&mut self, ctx: &Context, units: Units, keymap: &Keymap) {
         let Some(e_i) = self.selected_edge_i else {
             return;
         };
-        const CONSTRAINED: u8 = 1;
-        const BEZIER: u8 = 2;
-        const ARC: u8 = 4;
-        let mut mask = 0;
-        if self.polygon.vertices[e_i].edge_c.is_some() {
-            mask |= CONSTRAINED;
-        }
-        if self.polygon.vertices[e_i].bezier.is_some() {
-            mask |= BEZIER;
-        }
-        if self.polygon.vertices[e_i].arc.is_some() {
-            mask |= ARC;
-        }
+        let v = self.polygon.vertices[e_i];
+        let plain = v.edge_c.is_none() && v.bezier.is_none() && v.arc.is_none();
 
         let next_i = (e_i + 1) % self.polygon.vertices.len();
         let menu_pos = calc::midpoint(
             self.polygon.vertices[e_i].p,
             self.polygon.vertices[next_i].p,
-        ) + Vec2::splat(constants::SIZE_CONTEXT_MENU_OFFSET);
+        ) + Vec2::splat(constants::SIZE_CONTEXT_MENU_OFFSET.into());
         egui::containers::Area::new(constants::ID_EDGE_CONTEXT_MENU.into())
             .fixed_pos(menu_pos)
             .show(ctx, |ui| {
@@ -256,49 +940,46 @@ impl EditingState {
                     .fill(Color32::TRANSPARENT)
                     .show(ui, |ui| {
                         ui.set_min_width(constants::SIZE_CONTEXT_MENU);
-                        ui.spacing_mut().item_spacing = Vec2::ZERO;
+                        ui.spacing_mut().item_spacing = egui::Vec2::ZERO;
                         ui.with_layout(egui::Layout::top_down_justified(egui::Align::LEFT), |ui| {
-                            if mask == 0 {
-                                if ui
-                                    .add_enabled(
-                                        !self.polygon.has_vertical_neighbor(e_i),
-                                        Button::new("Make vertical"),
+                            let mut clicked = None;
+                            if plain {
+                                for (action, label) in [
+                                    (Action::MakeVertical, "Make vertical"),
+                                    (Action::MakeHorizontal, "Make horizontal"),
+                                    (Action::MakeDiagonalUp, "Make diagonal up [/]"),
+                                    (Action::MakeDiagonalDown, "Make diagonal down [\\]"),
+                                    (Action::ToBezier, "To Bézier segment"),
+                                    (Action::ToArc, "To circular arc"),
+                                ] {
+                                    if action_button(
+                                        ui,
+                                        keymap,
+                                        action,
+                                        label,
+                                        self.edge_action_enabled(e_i, action),
                                     )
                                     .clicked()
-                                {
-                                    self.polygon
-                                        .try_set_edge_constraint(e_i, EdgeConstraint::Vertical);
-                                    self.selected_edge_i = None;
+                                    {
+                                        clicked = Some(action);
+                                    }
                                 }
-                                if ui.add(Button::new("Make diagonal up [/]")).clicked() {
-                                    self.polygon
-                                        .try_set_edge_constraint(e_i, EdgeConstraint::DiagonalUp);
-                                    self.selected_edge_i = None;
-                                }
-                                if ui.add(Button::new("Make diagonal down [\\]")).clicked() {
-                                    self.polygon
-                                        .try_set_edge_constraint(e_i, EdgeConstraint::DiagonalDown);
-                                    self.selected_edge_i = None;
-                                }
-                                if ui.add(Button::new("To Bézier segment")).clicked() {
-                                    self.polygon.init_bezier(e_i);
-                                    self.polygon.vertices[e_i].edge_c = None;
-                                    self.polygon
-                                        .try_move_vertex(e_i, self.polygon.vertices[e_i].p);
-                                    self.selected_edge_i = None;
-                                }
-                                if ui.add(Button::new("To circular arc")).clicked() {
-                                    self.polygon.make_arc(e_i);
-                                    self.polygon.vertices[e_i].edge_c = None;
-                                    self.polygon
-                                        .try_move_vertex(e_i, self.polygon.vertices[e_i].p);
-                                    self.selected_edge_i = None;
-                                }
-                                let fix_length_btn = ui.add(Button::new("Fix length"));
-                                self.fixed_length_dialog.render(ui, &fix_length_btn);
-                                if fix_length_btn.clicked() {
-                                    self.fixed_length_dialog
-                                        .open(ui, self.polygon.edge_len(e_i));
+                                let fix_length_btn = action_button(
+                                    ui,
+                                    keymap,
+                                    Action::FixLength,
+                                    "Fix length",
+                                    true,
+                                );
+                                self.fixed_length_dialog.render(&fix_length_btn);
+                                if fix_length_btn.clicked()
+                                    || self.take_pending_dialog(Action::FixLength)
+                                {
+                                    self.fixed_length_dialog.open(
+                                        ui,
+                                        self.polygon.edge_len(e_i),
+                                        units,
+                                    );
                                 }
                                 if self.fixed_length_dialog.applied {
                                     let len = self.fixed_length_dialog.value;
@@ -310,32 +991,97 @@ impl EditingState {
                                     self.fixed_length_dialog.applied = false;
                                 }
                             }
-                            if ((mask & BEZIER) | (mask & ARC)) == 0 {
-                                if ui.add(Button::new("Subdivide")).clicked() {
-                                    self.polygon.subdivide_edge(e_i);
-                                    self.selected_edge_i = None;
+                            for (action, label) in [
+                                (Action::Subdivide, "Subdivide"),
+                                (Action::RemoveConstraint, "Remove constraint"),
+                                (Action::SplitCurve, "Split curve here"),
+                                (Action::RemoveBezier, "Remove Bézier segment"),
+                            ] {
+                                if self.edge_action_enabled(e_i, action)
+                                    && action_button(ui, keymap, action, label, true).clicked()
+                                {
+                                    clicked = Some(action);
                                 }
                             }
-                            if (mask & CONSTRAINED) > 0 {
-                                if ui.add(Button::new("Remove constraint")).clicked() {
-                                    self.polygon.vertices[e_i].edge_c = None;
-                                    self.selected_edge_i = None;
+                            if v.arc.is_some() {
+                                let set_radius_btn = action_button(
+                                    ui,
+                                    keymap,
+                                    Action::SetRadius,
+                                    "Set radius",
+                                    self.polygon.can_set_arc_radius(e_i),
+                                );
+                                self.arc_dialog.render(&set_radius_btn);
+                                if set_radius_btn.clicked()
+                                    || self.take_pending_dialog(Action::SetRadius)
+                                {
+                                    let arc = self.polygon.arc_data(e_i);
+                                    self.arc_dialog.open(
+                                        ui,
+                                        v.arc.unwrap_or_default(),
+                                        arc.radius,
+                                        self.polygon.edge_len(e_i) / 2.0,
+                                        units,
+                                    );
                                 }
-                            }
-                            if (mask & BEZIER) > 0 {
-                                if ui.add(Button::new("Remove Bézier segment")).clicked() {
-                                    self.polygon.vertices[e_i].bezier = None;
+                                if self.arc_dialog.applied {
+                                    self.polygon.try_set_arc(e_i, self.arc_dialog.arc);
                                     self.selected_edge_i = None;
+                                    self.arc_dialog.applied = false;
                                 }
                             }
-                            if (mask & ARC) > 0 {
-                                if ui.add(Button::new("Remove arc")).clicked() {
-                                    self.polygon.vertices[e_i].arc = None;
-                                    self.selected_edge_i = None;
+                            for (action, label) in [
+                                (Action::ResetRadius, "Reset radius"),
+                                (Action::RemoveArc, "Remove arc"),
+                            ] {
+                                if self.edge_action_enabled(e_i, action)
+                                    && action_button(ui, keymap, action, label, true).clicked()
+                                {
+                                    clicked = Some(action);
                                 }
                             }
+                            ui.separator();
+                            let dims = &mut self.polygon.vertices[e_i].dims;
+                            ui.checkbox(&mut dims.length, "Length dimension");
+                            ui.checkbox(&mut dims.angle, "Angle to previous edge");
+                            if v.arc.is_some() {
+                                ui.checkbox(&mut dims.radius, "Radius dimension");
+                            }
+                            if let Some(action) = clicked {
+                                self.apply_edge_action(e_i, action);
+                            }
                         });
                     });
             });
     }
+
+    // whether a shortcut asked to open the dialog of this action
+    fn take_pending_dialog(&mut self, action: Action) -> bool {
+        self.pending_dialog.take_if(|a| *a == action).is_some()
+    }
+
+    // splits the Bézier segment e_i where it's nearest to p
+    fn split_bezier_at(&mut self, e_i: usize, p: Pos2) {
+        if !self.polygon.is_bezier_start(e_i) {
+            return;
+        }
+        let t = self.polygon.segment(e_i).nearest_parameter(p);
+        self.polygon.split_bezier(e_i, t);
+    }
+}
+
+// a context menu button for an action, with its shortcut next to it
+fn action_button(
+    ui: &mut egui::Ui,
+    keymap: &Keymap,
+    action: Action,
+    label: &str,
+    enabled: bool,
+) -> egui::Response {
+    let mut button = Button::new(label);
+    if let Some(shortcut) = keymap.shortcut(action) {
+        button = button.shortcut_text(ui.ctx().format_shortcut(&shortcut));
+    }
+
+    ui.add_enabled(enabled, button)
 }